@@ -2,7 +2,7 @@ use classic_terraswap::querier::{query_balance, query_pair_info_from_pair};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, to_binary, Addr, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply,
+    coin, to_binary, Addr, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply,
     ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
 };
 use cw2::set_contract_version;
@@ -11,7 +11,7 @@ use cw20::Cw20ExecuteMsg;
 use crate::response::MsgInstantiateContractResponse;
 use crate::state::{
     add_allow_native_token, pair_key, read_pairs, Config, TmpPairInfo, ALLOW_NATIVE_TOKENS, CONFIG,
-    PAIRS, TMP_PAIR_INFO,
+    PAIRS, PAIR_KEYS_BY_LP, TMP_PAIR_INFO,
 };
 
 use classic_bindings::{TerraMsg, TerraQuery};
@@ -187,7 +187,11 @@ pub fn execute_create_pair(
                     token_code_id: config.token_code_id,
                     asset_decimals,
                     team_addr,
-                    clsm_addr: config.clsm_addr.to_string()
+                    clsm_addr: deps.api.addr_humanize(&config.clsm_addr)?.to_string(),
+                    commission_rate: None,
+                    protocol_fee_fraction: None,
+                    minimum_liquidity: None,
+                    measure_received: None,
                 })?,
             }),
             reply_on: ReplyOn::Success,
@@ -271,16 +275,22 @@ pub fn reply(deps: DepsMut<TerraQuery>, env: Env, msg: Reply) -> StdResult<Respo
         tmp_pair_info.assets[1].info.clone(),
     ];
 
+    let liquidity_token = deps.api.addr_canonicalize(&pair_info.liquidity_token)?;
     PAIRS.save(
         deps.storage,
         &tmp_pair_info.pair_key,
         &PairInfoRaw {
-            liquidity_token: deps.api.addr_canonicalize(&pair_info.liquidity_token)?,
+            liquidity_token: liquidity_token.clone(),
             contract_addr: deps.api.addr_canonicalize(pair_contract)?,
             asset_infos: raw_infos,
             asset_decimals: tmp_pair_info.asset_decimals,
         },
     )?;
+    PAIR_KEYS_BY_LP.save(
+        deps.storage,
+        liquidity_token.as_slice(),
+        &tmp_pair_info.pair_key,
+    )?;
 
     let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
     if !tmp_pair_info.assets[0].amount.is_zero() || !tmp_pair_info.assets[1].amount.is_zero() {
@@ -323,6 +333,7 @@ pub fn reply(deps: DepsMut<TerraQuery>, env: Env, msg: Reply) -> StdResult<Respo
                 receiver: Some(tmp_pair_info.sender.to_string()),
                 deadline: None,
                 slippage_tolerance: None,
+                min_lp_tokens: None,
             })?,
             funds,
         }));
@@ -347,6 +358,7 @@ pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> StdResult<Bina
         QueryMsg::NativeTokenDecimals { denom } => {
             to_binary(&query_native_token_decimal(deps, denom)?)
         }
+        QueryMsg::PairByLp { lp_token } => to_binary(&query_pair_by_lp(deps, lp_token)?),
     }
 }
 
@@ -370,6 +382,13 @@ pub fn query_pair(deps: Deps<TerraQuery>, asset_infos: [AssetInfo; 2]) -> StdRes
     pair_info.to_normal(deps.api)
 }
 
+pub fn query_pair_by_lp(deps: Deps<TerraQuery>, lp_token: String) -> StdResult<PairInfo> {
+    let lp_token = deps.api.addr_canonicalize(&lp_token)?;
+    let pair_key = PAIR_KEYS_BY_LP.load(deps.storage, lp_token.as_slice())?;
+    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    pair_info.to_normal(deps.api)
+}
+
 pub fn query_pairs(
     deps: Deps<TerraQuery>,
     start_after: Option<[AssetInfo; 2]>,
@@ -402,16 +421,31 @@ pub fn query_native_token_decimal(
 const TARGET_CONTRACT_VERSION: &str = "0.0.0";
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
-    deps: DepsMut<TerraQuery>,
+    mut deps: DepsMut<TerraQuery>,
     _env: Env,
     _msg: MigrateMsg,
 ) -> StdResult<Response<TerraMsg>> {
     migrate_version(
-        deps,
+        deps.branch(),
         TARGET_CONTRACT_VERSION,
         CONTRACT_NAME,
         CONTRACT_VERSION,
     )?;
 
+    // the pairs created before the LP token index are indexed too, a pair
+    // whose LP token was never stored has nothing to index
+    let pairs = PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, PairInfoRaw)>>>()?;
+    for (pair_key, pair_info) in pairs {
+        if !pair_info.liquidity_token.is_empty() {
+            PAIR_KEYS_BY_LP.save(
+                deps.storage,
+                pair_info.liquidity_token.as_slice(),
+                &pair_key,
+            )?;
+        }
+    }
+
     Ok(Response::default())
 }
@@ -26,6 +26,9 @@ pub struct TmpPairInfo {
 pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pair_info");
 
+// key : liquidity token / value: pair key
+pub const PAIR_KEYS_BY_LP: Map<&[u8], Vec<u8>> = Map::new("pair_keys_by_lp");
+
 pub fn pair_key(asset_infos: &[AssetInfoRaw; 2]) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
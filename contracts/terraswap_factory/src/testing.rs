@@ -1,13 +1,13 @@
 use classic_bindings::TerraQuery;
 
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use classic_terraswap::mock_querier::{mock_dependencies, WasmMockQuerier};
 
-use crate::state::{pair_key, TmpPairInfo, TMP_PAIR_INFO};
+use crate::state::{pair_key, TmpPairInfo, PAIRS, PAIR_KEYS_BY_LP, TMP_PAIR_INFO};
 
-use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
+use classic_terraswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw};
 use classic_terraswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, NativeTokenDecimalsResponse, QueryMsg,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, NativeTokenDecimalsResponse, QueryMsg,
 };
 use classic_terraswap::pair::{
     ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
@@ -15,9 +15,11 @@ use classic_terraswap::pair::{
 };
 use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, coins, from_binary, to_binary, Addr, CosmosMsg, OwnedDeps, Reply, ReplyOn,
-    Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coin, coins, from_binary, to_binary, Addr, Api, CanonicalAddr, CosmosMsg, Order,
+    OwnedDeps, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128,
+    WasmMsg,
 };
+use cw2::set_contract_version;
 use cw20::Cw20ExecuteMsg;
 
 #[test]
@@ -27,6 +29,7 @@ fn proper_initialization() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        clsm_addr: "clsm0000".to_string(),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -48,6 +51,7 @@ fn update_config() {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        clsm_addr: "clsm0000".to_string(),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -114,6 +118,7 @@ fn init(
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
         token_code_id: 123u64,
+        clsm_addr: "clsm0000".to_string(),
     };
 
     let env = mock_env();
@@ -181,7 +186,13 @@ fn create_pair() {
                         }
                     ],
                     token_code_id: 123u64,
-                    asset_decimals: [6u8, 8u8]
+                    asset_decimals: [6u8, 8u8],
+                    team_addr: "addr0000".to_string(),
+                    clsm_addr: "clsm0000".to_string(),
+                    commission_rate: None,
+                    protocol_fee_fraction: None,
+                    minimum_liquidity: None,
+                    measure_received: None,
                 })
                 .unwrap(),
                 code_id: 321u64,
@@ -269,7 +280,13 @@ fn create_pair_native_token_and_ibc_token() {
                         }
                     ],
                     token_code_id: 123u64,
-                    asset_decimals: [6u8, 6u8]
+                    asset_decimals: [6u8, 6u8],
+                    team_addr: "addr0000".to_string(),
+                    clsm_addr: "clsm0000".to_string(),
+                    commission_rate: None,
+                    protocol_fee_fraction: None,
+                    minimum_liquidity: None,
+                    measure_received: None,
                 })
                 .unwrap(),
                 code_id: 321u64,
@@ -492,6 +509,97 @@ fn reply_only_create_pair() {
     );
 }
 
+#[test]
+fn query_pair_by_lp_after_reply() {
+    let mut deps = mock_dependencies(&[]);
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+    ];
+
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+
+    let raw_assets = [
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::zero(),
+        }
+        .to_raw(deps.as_ref().api)
+        .unwrap(),
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: Uint128::zero(),
+        }
+        .to_raw(deps.as_ref().api)
+        .unwrap(),
+    ];
+
+    TMP_PAIR_INFO
+        .save(
+            &mut deps.storage,
+            &TmpPairInfo {
+                assets: raw_assets,
+                pair_key: pair_key(&raw_infos),
+                sender: Addr::unchecked("addr0000"),
+                asset_decimals: [8u8, 8u8],
+            },
+        )
+        .unwrap();
+
+    deps.querier.with_terraswap_factory(
+        &[(
+            &"0000".to_string(),
+            &PairInfo {
+                asset_infos: asset_infos.clone(),
+                contract_addr: "0000".to_string(),
+                liquidity_token: "liquidity0000".to_string(),
+                asset_decimals: [8u8, 8u8],
+            },
+        )],
+        &[],
+    );
+
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(vec![10, 4, 48, 48, 48, 48].into()),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByLp {
+            lp_token: "liquidity0000".to_string(),
+        },
+    )
+    .unwrap();
+    let pair_info: PairInfo = from_binary(&res).unwrap();
+    assert_eq!(pair_info.contract_addr, "0000");
+    assert_eq!(pair_info.liquidity_token, "liquidity0000");
+    assert_eq!(pair_info.asset_infos, asset_infos);
+
+    // an unknown liquidity token has no pair
+    query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByLp {
+            lp_token: "liquidity0001".to_string(),
+        },
+    )
+    .unwrap_err();
+}
+
 #[test]
 fn reply_create_pair_with_provide() {
     let mut deps = mock_dependencies(&[]);
@@ -623,6 +731,7 @@ fn reply_create_pair_with_provide() {
                     receiver: Some("addr0000".to_string()),
                     deadline: None,
                     slippage_tolerance: None,
+                    min_lp_tokens: None,
                 })
                 .unwrap(),
                 funds: coins(100u128, "uluna".to_string()),
@@ -834,3 +943,71 @@ fn failed_migrate_pair_with_no_admin() {
         Err(StdError::generic_err("unauthorized")),
     );
 }
+
+#[test]
+fn migrate_indexes_existing_pairs_by_lp() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    // a pair created before the LP token index, and one whose LP token
+    // was never stored
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+    ];
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+    let pair_info = PairInfoRaw {
+        liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+        contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+        asset_infos: raw_infos.clone(),
+        asset_decimals: [8u8, 8u8],
+    };
+    PAIRS
+        .save(&mut deps.storage, &pair_key(&raw_infos), &pair_info)
+        .unwrap();
+
+    let raw_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        }
+        .to_raw(deps.as_ref().api)
+        .unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+    let pending_pair_info = PairInfoRaw {
+        liquidity_token: CanonicalAddr::from(vec![]),
+        contract_addr: deps.api.addr_canonicalize("pair0001").unwrap(),
+        asset_infos: raw_infos.clone(),
+        asset_decimals: [6u8, 8u8],
+    };
+    PAIRS
+        .save(&mut deps.storage, &pair_key(&raw_infos), &pending_pair_info)
+        .unwrap();
+
+    set_contract_version(&mut deps.storage, "crates.io:terraswap-factory", "0.0.0").unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PairByLp {
+            lp_token: "liquidity0000".to_string(),
+        },
+    )
+    .unwrap();
+    let res: PairInfo = from_binary(&res).unwrap();
+    assert_eq!(res, pair_info.to_normal(deps.as_ref().api).unwrap());
+    assert_eq!(
+        PAIR_KEYS_BY_LP
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .count(),
+        1
+    );
+}
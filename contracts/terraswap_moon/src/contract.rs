@@ -1,49 +1,53 @@
 use crate::error::ContractError;
-use crate::response::MsgInstantiateContractResponse;
-use crate::state::MOON_CONFIG;
-use crate::util;
-use classic_terraswap::querier::{
-    query_balance, query_pair_info, query_token_balance
+use crate::state::{
+    read_emission_history, store_emission_record, MOON_CONFIG, PENDING_TIMER_TRIGGERS,
+    TOTAL_AUTOMATIC_BURNED, TOTAL_BURNED,
 };
+use crate::util;
+use crate::util::BURN_ADDRESS;
+use classic_terraswap::querier::{query_pool, query_token_balance};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Decimal256, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    Uint256, WasmMsg, WasmQuery
+    attr, from_binary, to_binary, Addr, Api, Attribute, Binary, CanonicalAddr, Coin, CosmosMsg,
+    Decimal, Deps, DepsMut, Env, MessageInfo, Order, OverflowError, OverflowOperation, Response,
+    StdError, StdResult, Uint128, WasmMsg,
 };
 
 use classic_bindings::{TerraMsg, TerraQuery};
 
-use classic_terraswap::asset::{Asset, AssetInfo, MoonInfo, MoonInfoRaw, VestInfo, VestInfoRaw};
+use classic_terraswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, MoonInfo, MoonInfoRaw, VestInfoRaw,
+};
 use classic_terraswap::moon::{
-    Cw20HookMsg, MoonExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse,
-    ReverseSimulationResponse, SimulationResponse,
+    AllEmissionStatusResponse, AutomaticBurnResponse, Bucket, BurnSource, BurnSourceResponse,
+    BurnStatsResponse, CanEmitResponse, ClsmPriceResponse, Cw20HookMsg, EmissionHistoryResponse,
+    EmissionRecord, EmissionScheduleEntry, EmissionScheduleResponse, EmissionStatus,
+    InstantiateMsg, MoonExecuteMsg, NextEmissionResponse, PriceBand, QueryMsg, TokenStatsResponse,
+    TotalBurnedResponse, VestingStatus, VestingStatusResponse,
 };
-use classic_terraswap::querier::query_token_info;
-use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
-use classic_terraswap::util::{assert_deadline, migrate_version};
+#[cfg(feature = "debug")]
+use classic_terraswap::moon::{RawAddress, RawConfigResponse};
+use classic_terraswap::pair::ExecuteMsg as PairExecuteMsg;
+use classic_terraswap::querier::{query_token_info, query_token_minter};
+use classic_terraswap::util::base_attrs;
 use cw2::set_contract_version;
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom, MinterResponse};
-use protobuf::Message;
-use std::cmp::Ordering;
-use std::convert::TryInto;
-use std::ops::Mul;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:terraswap-moon";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const INSTANTIATE_REPLY_ID: u64 = 1;
-
-/// Commission rate == 0.3%
-const COMMISSION_RATE: u64 = 3;
+/// Default minimum time between two emissions of a bucket == 30 days
+const DEFAULT_EMISSION_INTERVAL: u64 = 2_592_000;
 
-const MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
+/// Default highest mint ratio == 10
+const DEFAULT_MAX_MINT_RATIO: u64 = 10;
 
-const BURN_ADDRESS: &str = "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu";
+/// Default most months a bucket can carry forward == 6
+const DEFAULT_MAX_OWED_MONTHS: u128 = 6;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -51,9 +55,28 @@ pub fn instantiate(
     env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response<TerraMsg>> {
+) -> Result<Response<TerraMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    for (bucket, vest) in [
+        (Bucket::Pair, &msg.pair_vest),
+        (Bucket::Nft, &msg.nft_vest),
+        (Bucket::Marketing, &msg.marketing_vest),
+        (Bucket::Game, &msg.game_vest),
+        (Bucket::Team, &msg.team_vest),
+    ] {
+        if vest.monthly_amount.is_zero() || vest.month_count.is_zero() {
+            return Err(ContractError::InvalidVestingSchedule { bucket });
+        }
+    }
+
+    let clsm_addr = deps.api.addr_validate(&msg.clsm_addr)?;
+    let minter_addr = deps.api.addr_validate(&msg.minter_addr)?;
+    let timer_trigger = deps.api.addr_validate(&msg.timer_trigger)?;
+    if clsm_addr == minter_addr || clsm_addr == timer_trigger || minter_addr == timer_trigger {
+        return Err(ContractError::DuplicatedAddress {});
+    }
+
     let pair_vesting: VestInfoRaw = VestInfoRaw {
         address: deps
             .api
@@ -61,12 +84,32 @@ pub fn instantiate(
         monthly_amount: msg.pair_vest.monthly_amount,
         month_count: msg.pair_vest.month_count,
         month_index: Uint128::zero(),
+        last_emission_time: 0,
+        shortfall: Uint128::zero(),
+        months_owed: Uint128::zero(),
+        funding: Uint128::zero(),
+        asset_info: msg
+            .pair_vest
+            .asset_info
+            .as_ref()
+            .map(|asset_info| asset_info.to_raw(deps.api))
+            .transpose()?,
     };
     let nft_vesting: VestInfoRaw = VestInfoRaw {
         address: deps.api.addr_canonicalize(&msg.nft_vest.address.as_str())?,
         monthly_amount: msg.nft_vest.monthly_amount,
         month_count: msg.nft_vest.month_count,
         month_index: Uint128::zero(),
+        last_emission_time: 0,
+        shortfall: Uint128::zero(),
+        months_owed: Uint128::zero(),
+        funding: Uint128::zero(),
+        asset_info: msg
+            .nft_vest
+            .asset_info
+            .as_ref()
+            .map(|asset_info| asset_info.to_raw(deps.api))
+            .transpose()?,
     };
     let marketing_vesting: VestInfoRaw = VestInfoRaw {
         address: deps
@@ -75,6 +118,16 @@ pub fn instantiate(
         monthly_amount: msg.marketing_vest.monthly_amount,
         month_count: msg.marketing_vest.month_count,
         month_index: Uint128::zero(),
+        last_emission_time: 0,
+        shortfall: Uint128::zero(),
+        months_owed: Uint128::zero(),
+        funding: Uint128::zero(),
+        asset_info: msg
+            .marketing_vest
+            .asset_info
+            .as_ref()
+            .map(|asset_info| asset_info.to_raw(deps.api))
+            .transpose()?,
     };
     let game_vesting: VestInfoRaw = VestInfoRaw {
         address: deps
@@ -83,6 +136,16 @@ pub fn instantiate(
         monthly_amount: msg.game_vest.monthly_amount,
         month_count: msg.game_vest.month_count,
         month_index: Uint128::zero(),
+        last_emission_time: 0,
+        shortfall: Uint128::zero(),
+        months_owed: Uint128::zero(),
+        funding: Uint128::zero(),
+        asset_info: msg
+            .game_vest
+            .asset_info
+            .as_ref()
+            .map(|asset_info| asset_info.to_raw(deps.api))
+            .transpose()?,
     };
     let team_vesting: VestInfoRaw = VestInfoRaw {
         address: deps
@@ -91,10 +154,58 @@ pub fn instantiate(
         monthly_amount: msg.team_vest.monthly_amount,
         month_count: msg.team_vest.month_count,
         month_index: Uint128::zero(),
+        last_emission_time: 0,
+        shortfall: Uint128::zero(),
+        months_owed: Uint128::zero(),
+        funding: Uint128::zero(),
+        asset_info: msg
+            .team_vest
+            .asset_info
+            .as_ref()
+            .map(|asset_info| asset_info.to_raw(deps.api))
+            .transpose()?,
     };
 
+    assert_burn_divisors(msg.high_burn_divisor, msg.low_burn_divisor)?;
+
+    let max_mint_ratio = msg
+        .max_mint_ratio
+        .unwrap_or_else(|| Decimal::from_ratio(DEFAULT_MAX_MINT_RATIO, 1u64));
+    assert_mint_ratios(msg.lunc_mint_ratio, msg.ustc_mint_ratio, max_mint_ratio)?;
+    assert_price_band(&msg.mint_price_band)?;
+
+    let interval_seconds = msg.interval_seconds.unwrap_or(DEFAULT_EMISSION_INTERVAL);
+    if interval_seconds == 0 {
+        return Err(ContractError::InvalidEmissionInterval {});
+    }
+
     let moon_config: &MoonInfoRaw = &MoonInfoRaw {
-        clsm_addr: deps.api.addr_canonicalize(&msg.clsm_addr.as_str())?,
+        clsm_addr: deps.api.addr_canonicalize(clsm_addr.as_str())?,
+        authorized: vec![deps.api.addr_canonicalize(timer_trigger.as_str())?],
+        lunc_recipient: deps.api.addr_canonicalize(&msg.lunc_recipient.as_str())?,
+        minter_addr: deps.api.addr_canonicalize(minter_addr.as_str())?,
+        lunc_mint_ratio: msg.lunc_mint_ratio,
+        ustc_mint_ratio: msg.ustc_mint_ratio,
+        max_mint_ratio,
+        max_mint_amount: msg.max_mint_amount,
+        mint_price_band: msg.mint_price_band,
+        max_supply: msg.max_supply,
+        total_minted: Uint128::zero(),
+        allow_partial: msg.allow_partial.unwrap_or(false),
+        interval_seconds,
+        reserve_balance: msg.reserve_balance.unwrap_or_default(),
+        max_owed_months: msg
+            .max_owed_months
+            .unwrap_or_else(|| Uint128::from(DEFAULT_MAX_OWED_MONTHS)),
+        treasury_addr: deps.api.addr_canonicalize(&msg.treasury_addr.as_str())?,
+        burn_threshold: msg.burn_threshold,
+        high_burn_divisor: msg.high_burn_divisor,
+        low_burn_divisor: msg.low_burn_divisor,
+        burn_source: validate_burn_source(
+            deps.api,
+            msg.burn_source.unwrap_or(BurnSource::ContractSelf),
+        )?,
+        paused: false,
         pair_vest: pair_vesting,
         nft_vest: nft_vesting,
         marketing_vest: marketing_vesting,
@@ -114,274 +225,1835 @@ pub fn execute(
     msg: MoonExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        MoonExecuteMsg::VestingMint {} => vesting_mint(deps, env, info),
-        MoonExecuteMsg::DynamicMintFromLunc { amount, price } => lunc_dynamic_mint(deps, &env, info, amount, price),
-        MoonExecuteMsg::DynamicMintFromUstc { amount, price } => ustc_dynamic_mint(deps, &env, info, amount, price),
+        MoonExecuteMsg::VestingMint { to } => vesting_mint(deps, env, info, to),
+        MoonExecuteMsg::EmitAll { to } => emit_all(deps, env, info, to),
+        MoonExecuteMsg::Emit { bucket, to } => emission(deps, env, info, bucket, to),
+        MoonExecuteMsg::DynamicMintFromLunc { amount } => dynamic_mint_lunc(deps, env, info, amount),
+        MoonExecuteMsg::DynamicMintFromUstc { amount } => dynamic_mint_ustc(deps, env, info, amount),
+        MoonExecuteMsg::MintAndProvide {
+            clsm_amount,
+            pair_asset_amount,
+        } => mint_and_provide(deps, env, info, clsm_amount, pair_asset_amount),
+        MoonExecuteMsg::SendLunc { amount } => send_lunc(deps, env, info, amount),
+        MoonExecuteMsg::AutomaticBurn {} => automatic_burn(deps, env, info),
+        MoonExecuteMsg::SweepCompletedVesting { bucket } => {
+            sweep_completed_vesting(deps, env, info, bucket)
+        }
+        MoonExecuteMsg::UpdateMintRatios {
+            lunc_mint_ratio,
+            ustc_mint_ratio,
+        } => update_mint_ratios(deps, env, info, lunc_mint_ratio, ustc_mint_ratio),
+        MoonExecuteMsg::UpdateMintPriceBand { band } => {
+            update_mint_price_band(deps, env, info, band)
+        }
+        MoonExecuteMsg::UpdateBurnParams {
+            burn_threshold,
+            high_burn_divisor,
+            low_burn_divisor,
+        } => update_burn_params(
+            deps,
+            env,
+            info,
+            burn_threshold,
+            high_burn_divisor,
+            low_burn_divisor,
+        ),
+        MoonExecuteMsg::SetPaused { paused } => set_paused(deps, env, info, paused),
+        MoonExecuteMsg::EmergencyWithdraw { amount, to } => {
+            emergency_withdraw(deps, env, info, amount, to)
+        }
+        MoonExecuteMsg::UpdateTimerTrigger { new_trigger } => {
+            update_timer_trigger(deps, env, info, new_trigger)
+        }
+        MoonExecuteMsg::AcceptTimerTrigger {} => accept_timer_trigger(deps, env, info),
+        MoonExecuteMsg::AddAuthorized { address } => add_authorized(deps, env, info, address),
+        MoonExecuteMsg::RemoveAuthorized { address } => remove_authorized(deps, env, info, address),
+        MoonExecuteMsg::UpdateMinterAddr { new_minter } => {
+            update_minter_addr(deps, env, info, new_minter)
+        }
+        MoonExecuteMsg::UpdateVestRecipient {
+            bucket,
+            new_address,
+        } => update_vest_recipient(deps, env, info, bucket, new_address),
+        MoonExecuteMsg::SkipMonth { bucket } => skip_month(deps, env, info, bucket),
+        MoonExecuteMsg::RegisterFunding { bucket, amount } => {
+            register_funding(deps, env, info, bucket, amount)
+        }
+        MoonExecuteMsg::UpdateBurnSource { burn_source } => {
+            update_burn_source(deps, env, info, burn_source)
+        }
+        MoonExecuteMsg::VerifyMinter {} => verify_minter(deps, env, info),
+        MoonExecuteMsg::BatchTransfer { transfers } => batch_transfer(deps, env, info, transfers),
+        MoonExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
     }
 }
 
-pub fn vesting_mint(
+pub fn receive_cw20(
     deps: DepsMut<TerraQuery>,
     env: Env,
     info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    let mut messages: Vec<CosmosMsg> = vec![];
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Burn {} => {
+            let sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            holder_burn(deps, env, info, sender_addr, cw20_msg.amount)
+        }
+        Cw20HookMsg::SendToken { .. } => Err(ContractError::Unauthorized {}),
+    }
+}
+
+fn assert_authorized(
+    deps: &DepsMut<TerraQuery>,
+    moon_config: &MoonInfoRaw,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let sender = deps.api.addr_canonicalize(sender.as_str())?;
+    if !moon_config.authorized.contains(&sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+/// The pair triggers the vesting and the burn from its swaps, and the swap
+/// must not fail because of them
+fn is_pair(
+    deps: &DepsMut<TerraQuery>,
+    moon_config: &MoonInfoRaw,
+    sender: &Addr,
+) -> Result<bool, ContractError> {
+    let sender = deps.api.addr_canonicalize(sender.as_str())?;
+    Ok(sender == moon_config.pair_vest.address)
+}
+
+fn assert_not_paused(moon_config: &MoonInfoRaw) -> Result<(), ContractError> {
+    if moon_config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    Ok(())
+}
+
+pub fn set_paused(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    moon_config.paused = paused;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_paused"),
+        ("paused", &paused.to_string()),
+    ]))
+}
+
+/// Escape hatch moving the CLSM out while the contract is paused, capped
+/// by the balance of the contract
+pub fn emergency_withdraw(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    to: String,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    if !moon_config.paused {
+        return Err(ContractError::NotPaused {});
+    }
+
+    let to = deps.api.addr_validate(&to)?;
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+    let balance = query_token_balance(&deps.querier, clsm_addr.clone(), env.contract.address)?;
+    let amount = amount.min(balance);
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let message = util::transfer_token_message(Denom::Cw20(clsm_addr), amount, to.clone())?;
+
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        ("action", "emergency_withdraw"),
+        ("emergency", "true"),
+        ("to", to.as_str()),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+pub fn update_timer_trigger(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    new_trigger: String,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let new_trigger = deps.api.addr_validate(&new_trigger)?;
+    let new_trigger_raw = deps.api.addr_canonicalize(new_trigger.as_str())?;
+    if moon_config.authorized.contains(&new_trigger_raw) {
+        return Err(ContractError::DuplicatedAddress {});
+    }
+
+    // the sender keeps its authorization until the new address accepts
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    PENDING_TIMER_TRIGGERS.save(deps.storage, sender.as_slice(), &new_trigger_raw)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_timer_trigger"),
+        ("timer_trigger", info.sender.as_str()),
+        ("pending_timer_trigger", new_trigger.as_str()),
+    ]))
+}
+
+pub fn accept_timer_trigger(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    // only a proposed address can take over, the pending proposals are
+    // bounded by the authorized callers
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let old_trigger = PENDING_TIMER_TRIGGERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .find(|(_, pending)| *pending == sender)
+        .map(|(old_trigger, _)| CanonicalAddr::from(old_trigger))
+        .ok_or(ContractError::Unauthorized {})?;
+    // the sender may have been authorized since the proposal
+    if moon_config.authorized.contains(&sender) {
+        return Err(ContractError::DuplicatedAddress {});
+    }
+
+    for addr in moon_config.authorized.iter_mut() {
+        if *addr == old_trigger {
+            *addr = sender.clone();
+        }
+    }
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+    PENDING_TIMER_TRIGGERS.remove(deps.storage, old_trigger.as_slice());
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_timer_trigger"),
+        (
+            "old_timer_trigger",
+            deps.api.addr_humanize(&old_trigger)?.as_str(),
+        ),
+        ("new_timer_trigger", info.sender.as_str()),
+    ]))
+}
+
+pub fn add_authorized(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+    if moon_config.authorized.contains(&address_raw)
+        || address_raw == moon_config.clsm_addr
+        || address_raw == moon_config.minter_addr
+    {
+        return Err(ContractError::DuplicatedAddress {});
+    }
+
+    moon_config.authorized.push(address_raw);
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_authorized"),
+        ("address", address.as_str()),
+    ]))
+}
+
+pub fn remove_authorized(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+    if !moon_config.authorized.contains(&address_raw) {
+        return Err(ContractError::NotAuthorized {
+            address: address.to_string(),
+        });
+    }
+    // removing the last caller would leave the scheduled operations locked
+    if moon_config.authorized.len() == 1 {
+        return Err(ContractError::LastAuthorized {});
+    }
+
+    moon_config.authorized.retain(|addr| *addr != address_raw);
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+    // a revoked caller can't hand its authorization over anymore
+    PENDING_TIMER_TRIGGERS.remove(deps.storage, address_raw.as_slice());
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_authorized"),
+        ("address", address.as_str()),
+    ]))
+}
+
+pub fn update_minter_addr(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    new_minter: String,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let new_minter = deps.api.addr_validate(&new_minter)?;
+    let new_minter_raw = deps.api.addr_canonicalize(new_minter.as_str())?;
+    if new_minter_raw == moon_config.clsm_addr || moon_config.authorized.contains(&new_minter_raw) {
+        return Err(ContractError::DuplicatedAddress {});
+    }
+
+    let old_minter = stored_addr(deps.api, &moon_config.minter_addr, "minter_addr")?;
+    moon_config.minter_addr = new_minter_raw;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_minter_addr"),
+        ("old_minter_addr", old_minter.as_str()),
+        ("new_minter_addr", new_minter.as_str()),
+    ]))
+}
+
+pub fn register_funding(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    bucket: Bucket,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let vest = moon_config.vest_mut(bucket);
+    vest.funding = vest.funding.checked_add(amount)?;
+    let funding = vest.funding;
+
+    // every registered funding must be backed by the balance
+    let denom = vest_denom(deps.api, &moon_config, bucket)?;
+    let registered = registered_funding(&moon_config, &vest_asset(&moon_config, bucket), None)?;
+    let balance = util::get_token_amount(&deps.querier, denom, env.contract.address)?;
+    if registered > balance {
+        return Err(ContractError::FundingExceedsBalance {});
+    }
+
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "register_funding"),
+        ("bucket", &bucket.to_string()),
+        ("amount", &amount.to_string()),
+        ("funding", &funding.to_string()),
+    ]))
+}
+
+pub fn update_vest_recipient(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    bucket: Bucket,
+    new_address: String,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let new_address = deps.api.addr_validate(&new_address)?;
+    let vest = moon_config.vest_mut(bucket);
+    let old_address = stored_addr(deps.api, &vest.address, &format!("{}_vest.address", bucket))?;
+    vest.address = deps.api.addr_canonicalize(new_address.as_str())?;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_vest_recipient"),
+        ("bucket", &bucket.to_string()),
+        ("old_address", old_address.as_str()),
+        ("new_address", new_address.as_str()),
+    ]))
+}
 
+pub fn skip_month(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    bucket: Bucket,
+) -> Result<Response, ContractError> {
     let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
 
-    messages.push(emission2pair_contract(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2nft_minter(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2marketing(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2minigames(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2team(&deps, &env, &info, & mut moon_config)?);
+    let vest = moon_config.vest_mut(bucket);
+    if vest.month_index >= vest.month_count {
+        return Err(ContractError::VestingComplete { bucket });
+    }
 
+    // the emission interval is left as is, only the month is given up
+    vest.month_index = vest.month_index.checked_add(Uint128::from(1u8))?;
+    let month_index = vest.month_index;
     MOON_CONFIG.save(deps.storage, &moon_config)?;
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Response::new().add_attributes(vec![
+        ("action", "skip_month"),
+        ("bucket", &bucket.to_string()),
+        ("month_index", &month_index.to_string()),
+    ]))
+}
+
+/// Emits every bucket and fails when one of them cannot, except for the
+/// pair which only emits the buckets which are due
+pub fn vesting_mint(
+    mut deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    // the same callers as the single bucket emission, plus the pair
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    let from_pair = is_pair(&deps, &moon_config, &info.sender)?;
+    if !from_pair {
+        assert_authorized(&deps, &moon_config, &info.sender)?;
+    }
+    let to = emission_override(&deps, &info, to)?;
+
+    let mut balances: Vec<(Denom, Uint128)> = vec![];
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes: Vec<Attribute> = vec![];
+
+    for bucket in Bucket::all() {
+        let moon_config = MOON_CONFIG.load(deps.storage)?;
+        let denom = vest_denom(deps.api, &moon_config, bucket)?;
+        let balance = cached_balance(deps.as_ref(), &env, &mut balances, &denom)?;
+        let amount = match assert_can_emit(deps.as_ref(), &env, &moon_config, bucket, balance) {
+            Ok(amount) => amount,
+            // the pair fires on its own schedule, the buckets which are not
+            // due are left for a later call like in emit_all
+            Err(ContractError::VestingComplete { .. })
+            | Err(ContractError::EmissionTooSoon { .. })
+                if from_pair =>
+            {
+                continue
+            }
+            Err(err) => return Err(err),
+        };
+
+        let (message, emission_attributes) =
+            do_emission(deps.branch(), &env, bucket, Some(balance), to.as_ref())?;
+        spend_balance(&mut balances, &denom, amount);
+        messages.push(message);
+        attributes.extend(emission_attributes);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes)
+        .add_attributes(base_attrs()))
+}
+
+pub fn emission(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    bucket: Bucket,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    let to = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+
+    let (message, attributes) = do_emission(deps, &env, bucket, None, to.as_ref())?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(attributes)
+        .add_attributes(base_attrs()))
+}
+
+/// Emits every bucket which can emit, skipping the completed, not yet due
+/// and underfunded ones instead of failing the whole message
+pub fn emit_all(
+    mut deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    let to = to.map(|to| deps.api.addr_validate(&to)).transpose()?;
+
+    // the transfers are only executed after this message, so the balance
+    // read once per asset is reduced by every emission for the buckets to
+    // not share the same funds
+    let mut balances: Vec<(Denom, Uint128)> = vec![];
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes: Vec<Attribute> = vec![];
+    let mut emitted: Vec<String> = vec![];
+    let mut owed: Vec<String> = vec![];
+    for bucket in Bucket::all() {
+        // each emission saves the config, so it is reloaded for every bucket
+        let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+        let denom = vest_denom(deps.api, &moon_config, bucket)?;
+        let balance = cached_balance(deps.as_ref(), &env, &mut balances, &denom)?;
+
+        match assert_can_emit(deps.as_ref(), &env, &moon_config, bucket, balance) {
+            Ok(amount) => {
+                let (message, emission_attributes) =
+                    do_emission(deps.branch(), &env, bucket, Some(balance), to.as_ref())?;
+                spend_balance(&mut balances, &denom, amount);
+                messages.push(message);
+                attributes.extend(emission_attributes);
+                emitted.push(bucket.to_string());
+            }
+            // the month is carried forward for a later funded emission
+            Err(ContractError::LessThanVesting {})
+            | Err(ContractError::ReserveFloorViolation { .. }) => {
+                let max_owed_months = moon_config.max_owed_months;
+                if record_owed_month(
+                    moon_config.vest_mut(bucket),
+                    env.block.time.seconds(),
+                    max_owed_months,
+                )? {
+                    MOON_CONFIG.save(deps.storage, &moon_config)?;
+                    owed.push(bucket.to_string());
+                }
+            }
+            Err(ContractError::VestingComplete { .. })
+            | Err(ContractError::EmissionTooSoon { .. }) => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    if messages.is_empty() && owed.is_empty() {
+        return Err(ContractError::NothingToEmit {});
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("emitted_buckets", emitted.join(","))
+        .add_attribute("owed_buckets", owed.join(","))
+        .add_attributes(attributes)
+        .add_attributes(base_attrs()))
 }
 
-pub fn emission2pair_contract(
+/// Validated one-off recipient of the emissions, which only an authorized
+/// caller can set
+fn emission_override(
     deps: &DepsMut<TerraQuery>,
-    env: &Env,
     info: &MessageInfo,
-    moon_config:& mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let pair_contract_address = moon_config.pair_vest.address.clone();
-    let pair_contract_monthly_amount = moon_config.pair_vest.monthly_amount;
-    let pair_contract_month_count = moon_config.pair_vest.month_count;
-    let pair_contract_month_index = moon_config.pair_vest.month_index;
-
-    if pair_contract_month_index >= pair_contract_month_count {
-        return Err(ContractError::Unauthorized {});
+    to: Option<String>,
+) -> Result<Option<Addr>, ContractError> {
+    match to {
+        Some(to) => {
+            let moon_config = MOON_CONFIG.load(deps.storage)?;
+            assert_authorized(deps, &moon_config, &info.sender)?;
+            Ok(Some(deps.api.addr_validate(&to)?))
+        }
+        None => Ok(None),
     }
+}
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
+/// Humanizes an address of the stored config, naming the field when the
+/// stored value is corrupted
+fn stored_addr(api: &dyn Api, addr: &CanonicalAddr, field: &str) -> Result<Addr, ContractError> {
+    api.addr_humanize(addr)
+        .map_err(|_| ContractError::InvalidStoredAddress {
+            field: field.to_string(),
+        })
+}
+
+/// Asset vested by the bucket, CLSM unless configured otherwise
+fn vest_asset(moon_config: &MoonInfoRaw, bucket: Bucket) -> AssetInfoRaw {
+    moon_config
+        .vest(bucket)
+        .asset_info
+        .clone()
+        .unwrap_or_else(|| AssetInfoRaw::Token {
+            contract_addr: moon_config.clsm_addr.clone(),
+        })
+}
+
+fn vest_denom(
+    api: &dyn Api,
+    moon_config: &MoonInfoRaw,
+    bucket: Bucket,
+) -> Result<Denom, ContractError> {
+    Ok(match &moon_config.vest(bucket).asset_info {
+        Some(AssetInfoRaw::NativeToken { denom }) => Denom::Native(denom.to_string()),
+        Some(AssetInfoRaw::Token { contract_addr }) => Denom::Cw20(stored_addr(
+            api,
+            contract_addr,
+            &format!("{}_vest.asset_info", bucket),
+        )?),
+        None => Denom::Cw20(stored_addr(api, &moon_config.clsm_addr, "clsm_addr")?),
+    })
+}
+
+fn emission_attributes(bucket: Bucket, amount: Uint128, month_index: Uint128) -> Vec<Attribute> {
+    vec![
+        attr("action", "emission"),
+        attr("bucket", bucket.to_string()),
+        attr("amount", amount.to_string()),
+        attr("month_index", month_index.to_string()),
+    ]
+}
+
+/// Checks done before an emission of the bucket, without changing the state.
+/// Returns the amount to emit out of the `unspent` balance not registered
+/// for other buckets, covering the owed months when the balance allows it, and
+/// below the monthly amount only for a partial emission
+fn assert_can_emit(
+    deps: Deps<TerraQuery>,
+    env: &Env,
+    moon_config: &MoonInfoRaw,
+    bucket: Bucket,
+    unspent: Uint128,
+) -> Result<Uint128, ContractError> {
+    assert_not_paused(moon_config)?;
+
+    let vest = moon_config.vest(bucket);
+    if vest.month_index >= vest.month_count {
+        return Err(ContractError::VestingComplete { bucket });
+    }
+
+    assert_emission_interval(env, vest, moon_config.interval_seconds)?;
 
-    if clsm_amount < pair_contract_monthly_amount {
+    // a funded bucket draws on its own funding, the others on what is left
+    // once the funding of every other bucket is set aside
+    let available = if vest.funding.is_zero() {
+        unspent.saturating_sub(registered_funding(
+            moon_config,
+            &vest_asset(moon_config, bucket),
+            Some(bucket),
+        )?)
+    } else {
+        unspent.min(vest.funding)
+    };
+    let amount = emission_amount(vest, moon_config.allow_partial, available)?;
+    if amount.is_zero() {
         return Err(ContractError::LessThanVesting {});
     }
 
-    moon_config.pair_vest.month_index = pair_contract_month_index + Uint128::from(1 as u8);
+    // the reserve is kept out of every CLSM emission, whatever the bucket
+    let clsm = AssetInfoRaw::Token {
+        contract_addr: moon_config.clsm_addr.clone(),
+    };
+    if vest_asset(moon_config, bucket).equal(&clsm) {
+        let remaining = unspent - amount;
+        if remaining < moon_config.reserve_balance {
+            return Err(ContractError::ReserveFloorViolation {
+                reserve: moon_config.reserve_balance,
+                remaining,
+            });
+        }
+    }
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        pair_contract_monthly_amount,
-        deps.api.addr_humanize(&pair_contract_address)?,
-    )?)
+    Ok(amount)
 }
 
-pub fn emission2nft_minter(
-    deps: &DepsMut<TerraQuery>,
+/// The whole months the available balance funds, up to the current month
+/// and the owed ones, or the available balance itself for a partial emission
+fn emission_amount(
+    vest: &VestInfoRaw,
+    allow_partial: bool,
+    available: Uint128,
+) -> Result<Uint128, ContractError> {
+    let months = vest
+        .months_owed
+        .checked_add(Uint128::from(1u8))?
+        .min(vest.month_count.saturating_sub(vest.month_index));
+    let funded = months.min(
+        available
+            .checked_div(vest.monthly_amount)
+            .unwrap_or_default(),
+    );
+
+    Ok(if !funded.is_zero() {
+        funded.checked_mul(vest.monthly_amount)?
+    } else if allow_partial {
+        available
+    } else {
+        Uint128::zero()
+    })
+}
+
+/// Balance of the asset held by the contract, only queried the first time a
+/// handler emitting several buckets needs it
+fn cached_balance(
+    deps: Deps<TerraQuery>,
     env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let nft_minter_address = moon_config.nft_vest.address.clone();
-    let nft_minter_monthly_amount = moon_config.nft_vest.monthly_amount;
-    let nft_minter_month_count = moon_config.nft_vest.month_count;
-    let mut nft_minter_month_index = moon_config.nft_vest.month_index;
-
-    if nft_minter_month_index >= nft_minter_month_count {
-        return Err(ContractError::Unauthorized {});
+    balances: &mut Vec<(Denom, Uint128)>,
+    denom: &Denom,
+) -> Result<Uint128, ContractError> {
+    if let Some((_, balance)) = balances.iter().find(|(cached, _)| cached == denom) {
+        return Ok(*balance);
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
+    let balance =
+        util::get_token_amount(&deps.querier, denom.clone(), env.contract.address.clone())?;
+    balances.push((denom.clone(), balance));
 
-    if clsm_amount < nft_minter_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    Ok(balance)
+}
+
+/// Takes an emitted amount out of the cached balance of the asset
+fn spend_balance(balances: &mut [(Denom, Uint128)], denom: &Denom, amount: Uint128) {
+    for (cached, balance) in balances.iter_mut() {
+        if cached == denom {
+            *balance = balance.saturating_sub(amount);
+        }
     }
+}
 
-    // let all_nfts = WasmQuery::Smart {
-    //     contract_addr: nft_minter_address,
-    //     msg: to_binary(&QueryMsg::AllNftInfo { start_from: None, limit: None })?,
-    // };
-    // let all_nfts_response: AllNftInfoResponse = deps.querier.query(&all_nfts)?;
-    // let owners = all_nfts_response
-    //     .nfts
-    //     .into_iter()
-    //     .map(|nft_info| nft_info.owner)
-    //     .collect();
+/// Moves the bucket past the months the emission covered, recording how
+/// much a partial emission fell short of the monthly amount
+fn record_emission(
+    vest: &mut VestInfoRaw,
+    amount: Uint128,
+    block_time: u64,
+) -> Result<(), ContractError> {
+    let months = if amount > vest.monthly_amount {
+        amount
+            .checked_div(vest.monthly_amount)
+            .map_err(StdError::from)?
+    } else {
+        Uint128::from(1u8)
+    };
 
-    moon_config.nft_vest.month_index = nft_minter_month_index + Uint128::from(1 as u8);
+    vest.shortfall = vest
+        .shortfall
+        .checked_add(vest.monthly_amount.saturating_sub(amount))?;
+    vest.month_index = vest.month_index.checked_add(months)?;
+    vest.months_owed = vest.months_owed.saturating_sub(months - Uint128::from(1u8));
+    vest.funding = vest.funding.saturating_sub(amount);
+    vest.last_emission_time = block_time;
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        nft_minter_monthly_amount,
-        deps.api.addr_humanize(&nft_minter_address)?,
-    )?)
+    Ok(())
 }
 
-pub fn emission2marketing(
-    deps: &DepsMut<TerraQuery>,
+/// Carries the due month of an underfunded bucket forward, as long as the
+/// owed months still fit in the remaining schedule and under the cap.
+/// Returns whether it did, a month past the cap is not owed
+fn record_owed_month(
+    vest: &mut VestInfoRaw,
+    block_time: u64,
+    max_owed_months: Uint128,
+) -> StdResult<bool> {
+    let months_owed = vest.months_owed.checked_add(Uint128::from(1u8))?;
+    let remaining_months = vest.month_count.saturating_sub(vest.month_index);
+    if months_owed >= remaining_months || months_owed > max_owed_months {
+        return Ok(false);
+    }
+
+    vest.months_owed = months_owed;
+    vest.last_emission_time = block_time;
+
+    Ok(true)
+}
+
+/// Funding registered for the buckets vesting the asset, except the given one
+fn registered_funding(
+    moon_config: &MoonInfoRaw,
+    asset: &AssetInfoRaw,
+    except: Option<Bucket>,
+) -> StdResult<Uint128> {
+    let mut registered = Uint128::zero();
+    for bucket in Bucket::all() {
+        if Some(bucket) != except && vest_asset(moon_config, bucket).equal(asset) {
+            registered = registered.checked_add(moon_config.vest(bucket).funding)?;
+        }
+    }
+
+    Ok(registered)
+}
+
+/// Time from which the bucket can emit again, an interval too large to
+/// add to the last emission is an error rather than a panic
+fn next_emission_time(vest: &VestInfoRaw, interval_seconds: u64) -> Result<u64, ContractError> {
+    vest.last_emission_time
+        .checked_add(interval_seconds)
+        .ok_or_else(|| {
+            OverflowError::new(
+                OverflowOperation::Add,
+                vest.last_emission_time,
+                interval_seconds,
+            )
+            .into()
+        })
+}
+
+fn assert_emission_interval(
     env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let marketing_address = moon_config.marketing_vest.address.clone();
-    let marketing_monthly_amount = moon_config.marketing_vest.monthly_amount;
-    let marketing_month_count = moon_config.marketing_vest.month_count;
-    let marketing_month_index = moon_config.marketing_vest.month_index;
-
-    if marketing_month_index >= marketing_month_count {
-        return Err(ContractError::Unauthorized {});
+    vest: &VestInfoRaw,
+    interval_seconds: u64,
+) -> Result<(), ContractError> {
+    let next_available = next_emission_time(vest, interval_seconds)?;
+    if env.block.time.seconds() < next_available {
+        return Err(ContractError::EmissionTooSoon { next_available });
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
+    Ok(())
+}
+
+/// Emits the month of the bucket, saving the moved schedule before
+/// returning the transfer, which only runs once the message succeeds.
+/// `balance` is the unspent balance of the vested asset when the caller
+/// already read it, otherwise it is queried
+pub fn do_emission(
+    deps: DepsMut<TerraQuery>,
+    env: &Env,
+    bucket: Bucket,
+    balance: Option<Uint128>,
+    to: Option<&Addr>,
+) -> Result<(CosmosMsg, Vec<Attribute>), ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    let denom = vest_denom(deps.api, &moon_config, bucket)?;
+    let balance = match balance {
+        Some(balance) => balance,
+        None => util::get_token_amount(&deps.querier, denom.clone(), env.contract.address.clone())?,
+    };
+    let amount = assert_can_emit(deps.as_ref(), env, &moon_config, bucket, balance)?;
+
+    let vest = moon_config.vest_mut(bucket);
+    record_emission(vest, amount, env.block.time.seconds())?;
+    // the override only redirects this emission, the stored recipient is kept
+    let recipient = match to {
+        Some(to) => to.clone(),
+        None => stored_addr(deps.api, &vest.address, &format!("{}_vest.address", bucket))?,
+    };
+    let month_index = vest.month_index;
+
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+    store_emission_record(
+        deps.storage,
+        bucket,
+        &EmissionRecord {
+            month_index,
+            timestamp: env.block.time.seconds(),
+            amount,
+            recipient: recipient.clone(),
+        },
     )?;
 
-    if clsm_amount < marketing_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    let message = util::transfer_token_message(denom, amount, recipient)?;
+
+    Ok((message, emission_attributes(bucket, amount, month_index)))
+}
+
+pub fn dynamic_mint_lunc(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    dynamic_mint(
+        deps,
+        env,
+        info,
+        "uluna",
+        amount,
+        moon_config.lunc_mint_ratio,
+    )
+}
+
+pub fn dynamic_mint_ustc(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    dynamic_mint(deps, env, info, "uusd", amount, moon_config.ustc_mint_ratio)
+}
+
+fn assert_mint_ratios(
+    lunc_mint_ratio: Decimal,
+    ustc_mint_ratio: Decimal,
+    max_mint_ratio: Decimal,
+) -> Result<(), ContractError> {
+    for ratio in [lunc_mint_ratio, ustc_mint_ratio] {
+        if ratio > max_mint_ratio {
+            return Err(ContractError::InvalidMintRatio {
+                ratio,
+                max: max_mint_ratio,
+            });
+        }
     }
 
-    moon_config.marketing_vest.month_index = marketing_month_index + Uint128::from(1 as u8);
+    Ok(())
+}
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        marketing_monthly_amount,
-        deps.api.addr_humanize(&marketing_address)?,
-    )?)
+fn assert_price_band(band: &Option<PriceBand>) -> Result<(), ContractError> {
+    match band {
+        Some(band) if band.min > band.max => Err(ContractError::InvalidPriceBand {}),
+        _ => Ok(()),
+    }
 }
 
-pub fn emission2minigames(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let game_address = moon_config.game_vest.address.clone();
-    let game_monthly_amount = moon_config.game_vest.monthly_amount;
-    let game_month_count = moon_config.game_vest.month_count;
-    let game_month_index = moon_config.game_vest.month_index;
-
-    if game_month_index >= game_month_count {
-        return Err(ContractError::Unauthorized {});
+pub fn update_mint_ratios(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    lunc_mint_ratio: Decimal,
+    ustc_mint_ratio: Decimal,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    assert_mint_ratios(lunc_mint_ratio, ustc_mint_ratio, moon_config.max_mint_ratio)?;
+
+    moon_config.lunc_mint_ratio = lunc_mint_ratio;
+    moon_config.ustc_mint_ratio = ustc_mint_ratio;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_mint_ratios"),
+        ("lunc_mint_ratio", &lunc_mint_ratio.to_string()),
+        ("ustc_mint_ratio", &ustc_mint_ratio.to_string()),
+    ]))
+}
+
+pub fn update_mint_price_band(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    band: Option<PriceBand>,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    assert_price_band(&band)?;
+
+    let attributes = match &band {
+        Some(band) => vec![
+            ("action", "update_mint_price_band".to_string()),
+            ("min", band.min.to_string()),
+            ("max", band.max.to_string()),
+        ],
+        None => vec![("action", "update_mint_price_band".to_string())],
+    };
+    moon_config.mint_price_band = band;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Burns the sent native token and mints CLSM to the pair contract.
+/// The burned amount is valued at the pool price when the pair trades the
+/// denom, scaled by the mint ratio and capped by `max_mint_amount`. The
+/// pool price must be within `mint_price_band` when one is set
+pub fn dynamic_mint(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    denom: &str,
+    amount: Uint128,
+    mint_ratio: Decimal,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_not_paused(&moon_config)?;
+
+    Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        amount,
     }
+    .assert_sent_native_token_balance(&info)?;
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
+    let pair_contract_address = stored_addr(
+        deps.api,
+        &moon_config.pair_vest.address,
+        "pair_vest.address",
     )?;
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+    let pool = query_pool(&deps.querier, pair_contract_address.clone())?;
+    let reserve_of = |info: &AssetInfo| {
+        pool.assets
+            .iter()
+            .find(|asset| asset.info == *info)
+            .map(|asset| asset.amount)
+            .unwrap_or_default()
+    };
+    let denom_reserve = reserve_of(&AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    });
+    let clsm_reserve = reserve_of(&AssetInfo::Token {
+        contract_addr: clsm_addr.to_string(),
+    });
 
-    if clsm_amount < game_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    // without a pool price the ratio alone applies, a price skewed out of
+    // the band within the same transaction fails the mint
+    let pool_value = if denom_reserve.is_zero() || clsm_reserve.is_zero() {
+        amount
+    } else {
+        if let Some(band) = &moon_config.mint_price_band {
+            let price = Decimal::from_ratio(clsm_reserve, denom_reserve);
+            if price < band.min || price > band.max {
+                return Err(ContractError::PriceOutOfBand {
+                    price,
+                    min: band.min,
+                    max: band.max,
+                });
+            }
+        }
+        amount.multiply_ratio(clsm_reserve, denom_reserve)
+    };
+    let mint_amount = std::cmp::min(pool_value * mint_ratio, moon_config.max_mint_amount);
+    if mint_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
     }
 
-    moon_config.game_vest.month_index = game_month_index + Uint128::from(1 as u8);
+    let total_supply = query_token_info(&deps.querier, clsm_addr)?.total_supply;
+    let would_be = total_supply.checked_add(mint_amount)?;
+    if would_be > moon_config.max_supply {
+        return Err(ContractError::SupplyCapExceeded {
+            cap: moon_config.max_supply,
+            would_be,
+        });
+    }
+
+    moon_config.total_minted = moon_config.total_minted.checked_add(mint_amount)?;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    let messages: Vec<CosmosMsg> = vec![
+        util::transfer_token_message(
+            Denom::Native(denom.to_string()),
+            amount,
+            Addr::unchecked(BURN_ADDRESS),
+        )?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: stored_addr(deps.api, &moon_config.minter_addr, "minter_addr")?
+                .to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: pair_contract_address.to_string(),
+                amount: mint_amount,
+            })?,
+            funds: vec![],
+        }),
+    ];
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        game_monthly_amount,
-        deps.api.addr_humanize(&game_address)?,
-    )?)
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "dynamic_mint"),
+        ("denom", denom),
+        ("burn_amount", &amount.to_string()),
+        ("mint_amount", &mint_amount.to_string()),
+    ]))
 }
 
-pub fn emission2team(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let team_address = moon_config.team_vest.address.clone();
-    let team_monthly_amount = moon_config.team_vest.monthly_amount;
-    let team_month_count = moon_config.team_vest.month_count;
-    let team_month_index = moon_config.team_vest.month_index;
-
-    if team_month_index >= team_month_count {
+/// Mints CLSM to the contract and provides it to the pair with the other
+/// pool asset. The messages run in order and fail together, so the minted
+/// CLSM never stays in the contract when the provide fails
+pub fn mint_and_provide(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    clsm_amount: Uint128,
+    pair_asset_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !moon_config.authorized.contains(&sender) && sender != moon_config.minter_addr {
         return Err(ContractError::Unauthorized {});
     }
+    assert_not_paused(&moon_config)?;
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
+    if clsm_amount.is_zero() || pair_asset_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_contract_address = stored_addr(
+        deps.api,
+        &moon_config.pair_vest.address,
+        "pair_vest.address",
     )?;
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+    let clsm_info = AssetInfo::Token {
+        contract_addr: clsm_addr.to_string(),
+    };
+    let pool = query_pool(&deps.querier, pair_contract_address.clone())?;
+    let pair_asset_info = match &pool.assets {
+        [first, second] if first.info == clsm_info => second.info.clone(),
+        [first, second] if second.info == clsm_info => first.info.clone(),
+        _ => return Err(ContractError::AssetMismatch {}),
+    };
 
-    if clsm_amount < team_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    let total_supply = query_token_info(&deps.querier, clsm_addr.clone())?.total_supply;
+    let would_be = total_supply.checked_add(clsm_amount)?;
+    if would_be > moon_config.max_supply {
+        return Err(ContractError::SupplyCapExceeded {
+            cap: moon_config.max_supply,
+            would_be,
+        });
+    }
+
+    moon_config.total_minted = moon_config.total_minted.checked_add(clsm_amount)?;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: stored_addr(deps.api, &moon_config.minter_addr, "minter_addr")?.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: env.contract.address.to_string(),
+            amount: clsm_amount,
+        })?,
+        funds: vec![],
+    })];
+
+    // the pair pulls the tokens with TransferFrom and expects the native
+    // asset as funds
+    let mut funds: Vec<Coin> = vec![];
+    for asset in [
+        Asset {
+            info: clsm_info.clone(),
+            amount: clsm_amount,
+        },
+        Asset {
+            info: pair_asset_info.clone(),
+            amount: pair_asset_amount,
+        },
+    ] {
+        match asset.info {
+            AssetInfo::Token { contract_addr } => {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr,
+                    msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: pair_contract_address.to_string(),
+                        amount: asset.amount,
+                        expires: None,
+                    })?,
+                    funds: vec![],
+                }));
+            }
+            AssetInfo::NativeToken { denom } => funds.push(Coin {
+                denom,
+                amount: asset.amount,
+            }),
+        }
     }
 
-    moon_config.team_vest.month_index = team_month_index + Uint128::from(1 as u8);
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_contract_address.to_string(),
+        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: clsm_info,
+                    amount: clsm_amount,
+                },
+                Asset {
+                    info: pair_asset_info.clone(),
+                    amount: pair_asset_amount,
+                },
+            ],
+            receiver: None,
+            deadline: None,
+            slippage_tolerance: None,
+            min_lp_tokens: None,
+        })?,
+        funds,
+    }));
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        team_monthly_amount,
-        deps.api.addr_humanize(&team_address)?,
-    )?)
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "mint_and_provide"),
+        ("clsm_amount", &clsm_amount.to_string()),
+        ("pair_asset", &pair_asset_info.to_string()),
+        ("pair_asset_amount", &pair_asset_amount.to_string()),
+    ]))
 }
 
-pub fn lunc_dynamic_mint (
+pub fn send_lunc(
     deps: DepsMut<TerraQuery>,
-    env: &Env,
+    _env: Env,
     info: MessageInfo,
     amount: Uint128,
-    price: Decimal
 ) -> Result<Response, ContractError> {
     let moon_config = MOON_CONFIG.load(deps.storage)?;
-    let mut messages: Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let lunc_recipient = stored_addr(deps.api, &moon_config.lunc_recipient, "lunc_recipient")?;
+    let message = util::transfer_token_message(
         Denom::Native(String::from("uluna")),
         amount,
-        Addr::unchecked(BURN_ADDRESS)
+        lunc_recipient.clone(),
+    )?;
+
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        ("action", "send_lunc"),
+        ("recipient", lunc_recipient.as_str()),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+/// CLSM still to be emitted by all the buckets
+fn reserved_vesting_amount(
+    moon_config: &MoonInfoRaw,
+    asset: &AssetInfoRaw,
+) -> Result<Uint128, ContractError> {
+    let mut reserved = Uint128::zero();
+    for bucket in Bucket::all() {
+        if vest_asset(moon_config, bucket).equal(asset) {
+            reserved = reserved.checked_add(moon_config.vest(bucket).remaining_amount()?)?;
+        }
+    }
+
+    Ok(reserved)
+}
+
+/// Once the bucket has completed its vesting, the amount of its asset which
+/// is not reserved for the other buckets is sent to the treasury
+pub fn sweep_completed_vesting(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    bucket: Bucket,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let vest = moon_config.vest(bucket);
+    if vest.month_index < vest.month_count {
+        return Err(ContractError::VestingNotCompleted {});
+    }
+
+    let denom = vest_denom(deps.api, &moon_config, bucket)?;
+    let vest_amount = util::get_token_amount(&deps.querier, denom.clone(), env.contract.address)?;
+    let sweep_amount = vest_amount.saturating_sub(reserved_vesting_amount(
+        &moon_config,
+        &vest_asset(&moon_config, bucket),
     )?);
+    if sweep_amount.is_zero() {
+        return Err(ContractError::NothingToSweep {});
+    }
 
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps
-            .api
-            .addr_humanize(&moon_config.clsm_addr)?
-            .to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Mint {
-            recipient: info.sender.to_string(),
-            amount: amount * price,
-        })?,
-        funds: vec![],
-    }));
+    let treasury_addr = stored_addr(deps.api, &moon_config.treasury_addr, "treasury_addr")?;
+    let message = util::transfer_token_message(denom, sweep_amount, treasury_addr.clone())?;
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        ("action", "sweep_completed_vesting"),
+        ("bucket", &bucket.to_string()),
+        ("recipient", treasury_addr.as_str()),
+        ("amount", &sweep_amount.to_string()),
+    ]))
 }
 
-pub fn ustc_dynamic_mint (
+/// Sends the CLSM held above what the buckets still have to emit, one
+/// transfer per recipient
+pub fn batch_transfer(
     deps: DepsMut<TerraQuery>,
-    env: &Env,
+    env: Env,
     info: MessageInfo,
-    amount: Uint128,
-    price: Decimal
+    transfers: Vec<(String, Uint128)>,
 ) -> Result<Response, ContractError> {
     let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+    let mut total = Uint128::zero();
     let mut messages: Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(
-        Denom::Native(String::from("uusd")),
-        amount,
-        Addr::unchecked(BURN_ADDRESS)
+    for (recipient, amount) in transfers {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let recipient = deps.api.addr_validate(&recipient)?;
+        total = total.checked_add(amount)?;
+        messages.push(util::transfer_token_message(
+            Denom::Cw20(clsm_addr.clone()),
+            amount,
+            recipient,
+        )?);
+    }
+
+    let balance = query_token_balance(&deps.querier, clsm_addr, env.contract.address)?;
+    let surplus = balance.saturating_sub(reserved_vesting_amount(
+        &moon_config,
+        &AssetInfoRaw::Token {
+            contract_addr: moon_config.clsm_addr.clone(),
+        },
     )?);
+    if total > surplus {
+        return Err(ContractError::SurplusExceeded { total, surplus });
+    }
 
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps
-            .api
-            .addr_humanize(&moon_config.clsm_addr)?
-            .to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Mint {
-            recipient: info.sender.to_string(),
-            amount: amount * price,
-        })?,
-        funds: vec![],
-    }));
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "batch_transfer"),
+        ("total", &total.to_string()),
+        ("surplus", &surplus.to_string()),
+    ]))
+}
+
+/// Burns part of the circulating CLSM supply out of the CLSM held by this
+/// contract. The burn triggered by the pair does nothing while paused or
+/// when there is nothing to burn
+pub fn automatic_burn(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    if !is_pair(&deps, &moon_config, &info.sender)? {
+        assert_authorized(&deps, &moon_config, &info.sender)?;
+        return burn_from_source(deps, env, moon_config);
+    }
+
+    match burn_from_source(deps, env, moon_config) {
+        Err(err @ (ContractError::Paused {} | ContractError::NothingToBurn {})) => {
+            Ok(Response::new().add_attributes(vec![
+                ("action", "automatic_burn"),
+                ("skipped", &err.to_string()),
+            ]))
+        }
+        res => res,
+    }
+}
+
+fn burn_from_source(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    moon_config: MoonInfoRaw,
+) -> Result<Response, ContractError> {
+    assert_not_paused(&moon_config)?;
+
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+    let burn_owner = burn_owner(deps.api, &env, &moon_config)?;
+    let source_balance = query_token_balance(&deps.querier, clsm_addr.clone(), burn_owner.clone())?;
+    if source_balance.is_zero() {
+        return Err(ContractError::NothingToBurn {});
+    }
+
+    let total_supply = query_circulating_supply(deps.as_ref(), &env, clsm_addr.clone())?;
+    let burn_amount = next_burn_amount(&moon_config, total_supply, source_balance)?;
+    if burn_amount.is_zero() {
+        return Err(ContractError::NothingToBurn {});
+    }
+    let branch = if total_supply >= moon_config.burn_threshold {
+        "high"
+    } else {
+        "low"
+    };
+
+    // any holder other than the contract itself is burned through its allowance
+    let burn_msg = if burn_owner == env.contract.address {
+        Cw20ExecuteMsg::Burn {
+            amount: burn_amount,
+        }
+    } else {
+        util::assert_allowance(
+            &deps.querier,
+            clsm_addr.clone(),
+            burn_owner.clone(),
+            env.contract.address.clone(),
+            burn_amount,
+        )?;
+        Cw20ExecuteMsg::BurnFrom {
+            owner: burn_owner.to_string(),
+            amount: burn_amount,
+        }
+    };
+
+    let automatic_burned = TOTAL_AUTOMATIC_BURNED
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_add(burn_amount)?;
+    TOTAL_AUTOMATIC_BURNED.save(deps.storage, &automatic_burned)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: clsm_addr.to_string(),
+            msg: to_binary(&burn_msg)?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            ("action", "automatic_burn"),
+            ("total_supply", &total_supply.to_string()),
+            ("burn_amount", &burn_amount.to_string()),
+            ("branch", branch),
+        ])
+        .add_attributes(base_attrs())
+        .set_data(to_binary(&AutomaticBurnResponse { burn_amount })?))
+}
+
+/// Address the automatic burn takes the CLSM from
+fn burn_owner(api: &dyn Api, env: &Env, moon_config: &MoonInfoRaw) -> Result<Addr, ContractError> {
+    Ok(match &moon_config.burn_source {
+        BurnSource::ContractSelf => env.contract.address.clone(),
+        BurnSource::Pair => stored_addr(api, &moon_config.pair_vest.address, "pair_vest.address")?,
+        BurnSource::Custom(addr) => addr.clone(),
+    })
+}
+
+fn validate_burn_source(api: &dyn Api, burn_source: BurnSource) -> StdResult<BurnSource> {
+    Ok(match burn_source {
+        BurnSource::Custom(addr) => BurnSource::Custom(api.addr_validate(addr.as_str())?),
+        burn_source => burn_source,
+    })
+}
+
+/// Amount the next automatic burn takes out of the burn source balance
+fn next_burn_amount(
+    moon_config: &MoonInfoRaw,
+    total_supply: Uint128,
+    source_balance: Uint128,
+) -> Result<Uint128, ContractError> {
+    let burn_divisor = if total_supply >= moon_config.burn_threshold {
+        moon_config.high_burn_divisor
+    } else {
+        moon_config.low_burn_divisor
+    };
+    let burn_amount = total_supply
+        .checked_div(burn_divisor)
+        .map_err(|_| ContractError::InvalidBurnDivisor {})?;
+
+    // never burn more than the circulating supply nor the held balance
+    Ok(burn_amount.min(total_supply).min(source_balance))
+}
+
+fn assert_burn_divisors(
+    high_burn_divisor: Uint128,
+    low_burn_divisor: Uint128,
+) -> Result<(), ContractError> {
+    if high_burn_divisor.is_zero() || low_burn_divisor.is_zero() {
+        return Err(ContractError::InvalidBurnDivisor {});
+    }
+
+    Ok(())
+}
+
+pub fn update_burn_params(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    burn_threshold: Uint128,
+    high_burn_divisor: Uint128,
+    low_burn_divisor: Uint128,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+    assert_burn_divisors(high_burn_divisor, low_burn_divisor)?;
+
+    moon_config.burn_threshold = burn_threshold;
+    moon_config.high_burn_divisor = high_burn_divisor;
+    moon_config.low_burn_divisor = low_burn_divisor;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_burn_params"),
+        ("burn_threshold", &burn_threshold.to_string()),
+        ("high_burn_divisor", &high_burn_divisor.to_string()),
+        ("low_burn_divisor", &low_burn_divisor.to_string()),
+    ]))
+}
+
+pub fn update_burn_source(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    burn_source: BurnSource,
+) -> Result<Response, ContractError> {
+    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_authorized(&deps, &moon_config, &info.sender)?;
+
+    moon_config.burn_source = validate_burn_source(deps.api, burn_source)?;
+    MOON_CONFIG.save(deps.storage, &moon_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_burn_source"),
+        ("burn_source", &moon_config.burn_source.to_string()),
+    ]))
+}
+
+/// Burns the CLSM a holder sent through the receive hook and adds it to the
+/// public burn tally
+pub fn holder_burn(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    assert_not_paused(&moon_config)?;
+
+    // only the CLSM token can execute this message
+    if deps.api.addr_canonicalize(info.sender.as_str())? != moon_config.clsm_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let total_burned = TOTAL_BURNED
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_add(amount)?;
+    TOTAL_BURNED.save(deps.storage, &total_burned)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: info.sender.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            ("action", "holder_burn"),
+            ("sender", sender.as_str()),
+            ("amount", &amount.to_string()),
+            ("total_burned", &total_burned.to_string()),
+        ])
+        .add_attributes(base_attrs()))
+}
+
+/// Fails when the CLSM token is not minted by the configured minter, which
+/// the mint messages are sent to, so a misconfiguration surfaces before a
+/// dynamic mint
+pub fn verify_minter(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    _info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+    let minter_addr = stored_addr(deps.api, &moon_config.minter_addr, "minter_addr")?;
+
+    let minter = query_token_minter(&deps.querier, clsm_addr)?
+        .map(|res| res.minter)
+        .unwrap_or_default();
+    if minter != minter_addr.as_str() {
+        return Err(ContractError::MinterMismatch {
+            expected: minter_addr.to_string(),
+            actual: minter,
+        });
+    }
+
+    Ok(Response::new().add_attributes(vec![("action", "verify_minter"), ("minter", &minter)]))
+}
+
+/// CLSM supply excluding the balances of this contract and the burn address
+pub fn query_circulating_supply(
+    deps: Deps<TerraQuery>,
+    env: &Env,
+    clsm_addr: Addr,
+) -> StdResult<Uint128> {
+    let total_token = query_token_info(&deps.querier, clsm_addr.clone())?.total_supply;
+    let total_moon = query_token_balance(
+        &deps.querier,
+        clsm_addr.clone(),
+        env.contract.address.clone(),
+    )?;
+    let total_burn = query_token_balance(&deps.querier, clsm_addr, Addr::unchecked(BURN_ADDRESS))?;
+
+    Ok(total_token
+        .checked_sub(total_moon)?
+        .checked_sub(total_burn)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<TerraQuery>, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_moon_info(deps)?)?),
+        QueryMsg::VestingStatus {} => Ok(to_binary(&query_vesting_status(deps)?)?),
+        QueryMsg::CanEmit { bucket } => Ok(to_binary(&query_can_emit(deps, env, bucket)?)?),
+        QueryMsg::NextEmission { bucket } => {
+            Ok(to_binary(&query_next_emission(deps, env, bucket)?)?)
+        }
+        QueryMsg::AllEmissionStatus {} => Ok(to_binary(&query_all_emission_status(deps, env)?)?),
+        QueryMsg::TokenStats {} => Ok(to_binary(&query_token_stats(deps, env)?)?),
+        QueryMsg::ClsmPrice {} => Ok(to_binary(&query_clsm_price(deps)?)?),
+        QueryMsg::BurnSource {} => Ok(to_binary(&query_burn_source(deps)?)?),
+        QueryMsg::BurnStats {} => Ok(to_binary(&query_burn_stats(deps)?)?),
+        QueryMsg::TotalBurned {} => Ok(to_binary(&query_total_burned(deps)?)?),
+        QueryMsg::EmissionSchedule {
+            bucket,
+            max_entries,
+        } => Ok(to_binary(&query_emission_schedule(
+            deps,
+            bucket,
+            max_entries,
+        )?)?),
+        QueryMsg::EmissionHistory {
+            bucket,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_emission_history(
+            deps,
+            bucket,
+            start_after,
+            limit,
+        )?)?),
+        #[cfg(feature = "debug")]
+        QueryMsg::RawConfig {} => Ok(to_binary(&query_raw_config(deps)?)?),
+    }
+}
+
+#[cfg(feature = "debug")]
+pub fn query_raw_config(deps: Deps<TerraQuery>) -> Result<RawConfigResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    let mut fields: Vec<(String, &CanonicalAddr)> = vec![
+        ("clsm_addr".to_string(), &moon_config.clsm_addr),
+        ("lunc_recipient".to_string(), &moon_config.lunc_recipient),
+        ("minter_addr".to_string(), &moon_config.minter_addr),
+        ("treasury_addr".to_string(), &moon_config.treasury_addr),
+    ];
+    for addr in moon_config.authorized.iter() {
+        fields.push(("authorized".to_string(), addr));
+    }
+    for bucket in Bucket::all() {
+        let vest = moon_config.vest(bucket);
+        fields.push((format!("{}_vest", bucket), &vest.address));
+    }
+
+    // a misconfigured address is reported rather than failing the query
+    let addresses = fields
+        .into_iter()
+        .map(|(field, addr)| RawAddress {
+            field,
+            canonical: Binary::from(addr.as_slice()),
+            humanized: deps.api.addr_humanize(addr).ok(),
+        })
+        .collect();
+
+    Ok(RawConfigResponse { addresses })
+}
+
+pub fn query_burn_stats(deps: Deps<TerraQuery>) -> Result<BurnStatsResponse, ContractError> {
+    let total_burned = TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(BurnStatsResponse { total_burned })
+}
+
+pub fn query_total_burned(deps: Deps<TerraQuery>) -> Result<TotalBurnedResponse, ContractError> {
+    let automatic_burned = TOTAL_AUTOMATIC_BURNED
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let voluntary_burned = TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(TotalBurnedResponse {
+        total_burned: automatic_burned.checked_add(voluntary_burned)?,
+        automatic_burned,
+        voluntary_burned,
+    })
+}
+
+pub fn query_moon_info(deps: Deps<TerraQuery>) -> Result<MoonInfo, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    Ok(moon_config.to_normal(deps.api)?)
+}
+
+pub fn query_can_emit(
+    deps: Deps<TerraQuery>,
+    env: Env,
+    bucket: Bucket,
+) -> Result<CanEmitResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    let denom = vest_denom(deps.api, &moon_config, bucket)?;
+    let can_emit = util::get_token_amount(&deps.querier, denom, env.contract.address.clone())
+        .and_then(|balance| assert_can_emit(deps, &env, &moon_config, bucket, balance));
+    Ok(match can_emit {
+        Ok(amount) => CanEmitResponse {
+            ok: true,
+            reason: None,
+            amount,
+        },
+        Err(err) => CanEmitResponse {
+            ok: false,
+            reason: Some(err.to_string()),
+            amount: moon_config.vest(bucket).monthly_amount,
+        },
+    })
+}
+
+pub fn query_all_emission_status(
+    deps: Deps<TerraQuery>,
+    env: Env,
+) -> Result<AllEmissionStatusResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    // each asset balance is read once for all the buckets vesting it
+    let mut balances: Vec<(Denom, Uint128)> = vec![];
+
+    let mut statuses: Vec<EmissionStatus> = vec![];
+    for bucket in Bucket::all() {
+        let denom = vest_denom(deps.api, &moon_config, bucket)?;
+        let can_emit = cached_balance(deps, &env, &mut balances, &denom)
+            .and_then(|balance| assert_can_emit(deps, &env, &moon_config, bucket, balance));
+        statuses.push(match can_emit {
+            Ok(amount) => {
+                spend_balance(&mut balances, &denom, amount);
+                EmissionStatus {
+                    bucket,
+                    ready: true,
+                    amount,
+                    reason: None,
+                }
+            }
+            Err(err) => EmissionStatus {
+                bucket,
+                ready: false,
+                amount: moon_config.vest(bucket).monthly_amount,
+                reason: Some(err.to_string()),
+            },
+        });
+    }
+
+    Ok(AllEmissionStatusResponse { statuses })
+}
+
+pub fn query_next_emission(
+    deps: Deps<TerraQuery>,
+    env: Env,
+    bucket: Bucket,
+) -> Result<NextEmissionResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    let next_available =
+        next_emission_time(moon_config.vest(bucket), moon_config.interval_seconds)?;
+    let seconds_remaining = next_available.saturating_sub(env.block.time.seconds());
+    Ok(NextEmissionResponse {
+        ready: seconds_remaining == 0,
+        seconds_remaining,
+    })
+}
+
+pub fn query_burn_source(deps: Deps<TerraQuery>) -> Result<BurnSourceResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    Ok(BurnSourceResponse {
+        burn_source: moon_config.burn_source,
+    })
+}
+
+pub fn query_clsm_price(deps: Deps<TerraQuery>) -> Result<ClsmPriceResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    let pair_contract_address = stored_addr(
+        deps.api,
+        &moon_config.pair_vest.address,
+        "pair_vest.address",
+    )?;
+    let pool = query_pool(&deps.querier, pair_contract_address)?;
+
+    let clsm_info = AssetInfo::Token {
+        contract_addr: stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?.to_string(),
+    };
+    let (clsm_asset, quote_asset) = match &pool.assets {
+        [first, second] if first.info == clsm_info => (first, second),
+        [first, second] if second.info == clsm_info => (second, first),
+        _ => return Err(ContractError::AssetMismatch {}),
+    };
+
+    if clsm_asset.amount.is_zero() || quote_asset.amount.is_zero() {
+        return Err(ContractError::NoLiquidity {});
+    }
+
+    Ok(ClsmPriceResponse {
+        price: Decimal::from_ratio(quote_asset.amount, clsm_asset.amount),
+        quote_asset_info: quote_asset.info.clone(),
+    })
+}
+
+pub fn query_emission_schedule(
+    deps: Deps<TerraQuery>,
+    bucket: Bucket,
+    max_entries: u32,
+) -> Result<EmissionScheduleResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    let vest = moon_config.vest(bucket);
+
+    let entries = (vest.month_index.u128()..vest.month_count.u128())
+        .take(max_entries as usize)
+        .map(|month_index| EmissionScheduleEntry {
+            month_index: Uint128::from(month_index),
+            amount: vest.monthly_amount,
+        })
+        .collect();
+
+    Ok(EmissionScheduleResponse { entries })
+}
+
+pub fn query_emission_history(
+    deps: Deps<TerraQuery>,
+    bucket: Bucket,
+    start_after: Option<Uint128>,
+    limit: Option<u32>,
+) -> Result<EmissionHistoryResponse, ContractError> {
+    let records = read_emission_history(deps.storage, bucket, start_after, limit)?;
+
+    Ok(EmissionHistoryResponse { records })
+}
+
+pub fn query_token_stats(
+    deps: Deps<TerraQuery>,
+    env: Env,
+) -> Result<TokenStatsResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    let clsm_addr = stored_addr(deps.api, &moon_config.clsm_addr, "clsm_addr")?;
+
+    let contract_balance = query_token_balance(
+        &deps.querier,
+        clsm_addr.clone(),
+        env.contract.address.clone(),
+    )?;
+    let source_balance = query_token_balance(
+        &deps.querier,
+        clsm_addr.clone(),
+        burn_owner(deps.api, &env, &moon_config)?,
+    )?;
+    let total_supply = query_circulating_supply(deps, &env, clsm_addr)?;
+
+    Ok(TokenStatsResponse {
+        total_supply,
+        contract_balance,
+        next_burn_amount: next_burn_amount(&moon_config, total_supply, source_balance)?,
+    })
+}
+
+pub fn query_vesting_status(
+    deps: Deps<TerraQuery>,
+) -> Result<VestingStatusResponse, ContractError> {
+    let moon_config = MOON_CONFIG.load(deps.storage)?;
+
+    Ok(VestingStatusResponse {
+        pair: vesting_status(&moon_config.pair_vest)?,
+        nft: vesting_status(&moon_config.nft_vest)?,
+        marketing: vesting_status(&moon_config.marketing_vest)?,
+        game: vesting_status(&moon_config.game_vest)?,
+        team: vesting_status(&moon_config.team_vest)?,
+    })
+}
 
-    Ok(Response::new().add_messages(messages))
+fn vesting_status(vest: &VestInfoRaw) -> Result<VestingStatus, ContractError> {
+    Ok(VestingStatus {
+        month_index: vest.month_index,
+        month_count: vest.month_count,
+        monthly_amount: vest.monthly_amount,
+        remaining: vest.remaining_amount()?,
+        shortfall: vest.shortfall,
+        months_owed: vest.months_owed,
+        funding: vest.funding,
+    })
 }
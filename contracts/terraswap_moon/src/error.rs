@@ -1,4 +1,5 @@
-use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError};
+use classic_terraswap::moon::Bucket;
+use cosmwasm_std::{ConversionOverflowError, Decimal, OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -15,15 +16,97 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("CLSM minter mismatch: expected {expected}, got {actual}")]
+    MinterMismatch { expected: String, actual: String },
+
+    #[error("Invalid stored address for {field}")]
+    InvalidStoredAddress { field: String },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Contract is not paused")]
+    NotPaused {},
+
     #[error("Not Reward or Order token")]
     UnacceptableToken {},
 
     #[error("Less CLSM amount than vesting amount")]
     LessThanVesting {},
 
+    #[error("Vesting of the {bucket} bucket is complete")]
+    VestingComplete { bucket: Bucket },
+
+    #[error("Emission is not available until {next_available}")]
+    EmissionTooSoon { next_available: u64 },
+
+    #[error("Emission would leave {remaining} CLSM, below the {reserve} reserve")]
+    ReserveFloorViolation {
+        reserve: Uint128,
+        remaining: Uint128,
+    },
+
+    #[error("Nothing to emit")]
+    NothingToEmit {},
+
+    #[error("Registered funding exceeds the balance")]
+    FundingExceedsBalance {},
+
+    #[error("Batch transfer exceeds the surplus ({total} > {surplus})")]
+    SurplusExceeded { total: Uint128, surplus: Uint128 },
+
     #[error("Invalid zero amount")]
     InvalidZeroAmount {},
 
+    #[error("Supply cap exceeded ({would_be} > {cap})")]
+    SupplyCapExceeded { cap: Uint128, would_be: Uint128 },
+
+    #[error("Nothing to burn")]
+    NothingToBurn {},
+
+    #[error("No liquidity in the pair")]
+    NoLiquidity {},
+
+    #[error("Invalid mint ratio {ratio}, the maximum is {max}")]
+    InvalidMintRatio { ratio: Decimal, max: Decimal },
+
+    #[error("Invalid price band, the minimum is above the maximum")]
+    InvalidPriceBand {},
+
+    #[error("Pool price {price} is out of the {min} - {max} band")]
+    PriceOutOfBand {
+        price: Decimal,
+        min: Decimal,
+        max: Decimal,
+    },
+
+    #[error("Invalid burn divisor")]
+    InvalidBurnDivisor {},
+
+    #[error("Invalid emission interval")]
+    InvalidEmissionInterval {},
+
+    #[error("Invalid vesting schedule for the {bucket} bucket")]
+    InvalidVestingSchedule { bucket: Bucket },
+
+    #[error("CLSM, minter and timer trigger addresses must be distinct")]
+    DuplicatedAddress {},
+
+    #[error("{address} is not an authorized caller")]
+    NotAuthorized { address: String },
+
+    #[error("The last authorized caller can't be removed")]
+    LastAuthorized {},
+
+    #[error("Insufficient allowance ({needed} > {granted})")]
+    InsufficientAllowance { needed: Uint128, granted: Uint128 },
+
+    #[error("Vesting is not completed")]
+    VestingNotCompleted {},
+
+    #[error("Nothing to sweep")]
+    NothingToSweep {},
+
     #[error("Max spread assertion")]
     MaxSpreadAssertion {},
 
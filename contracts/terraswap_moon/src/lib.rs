@@ -3,7 +3,6 @@ pub mod state;
 pub mod util;
 
 mod error;
-mod response;
 
 #[cfg(test)]
 mod testing;
@@ -1,4 +1,52 @@
 use classic_terraswap::asset::MoonInfoRaw;
-use cw_storage_plus::Item;
+use classic_terraswap::moon::{Bucket, EmissionRecord};
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
 
 pub const MOON_CONFIG: Item<MoonInfoRaw> = Item::new("moon_config");
+
+// key : authorized caller / value: address proposed to replace it
+// a later proposal of the same caller replaces the pending one
+pub const PENDING_TIMER_TRIGGERS: Map<&[u8], CanonicalAddr> = Map::new("pending_timer_triggers");
+
+/// CLSM burned by the holders through the receive hook
+pub const TOTAL_BURNED: Item<Uint128> = Item::new("total_burned");
+
+/// CLSM burned by the automatic burn, whatever its source
+pub const TOTAL_AUTOMATIC_BURNED: Item<Uint128> = Item::new("total_automatic_burned");
+
+// key : (bucket, month index) / value: emission record
+// one record per month at most, so the history is bounded by month_count
+pub const EMISSION_HISTORY: Map<(&str, u128), EmissionRecord> = Map::new("emission_history");
+
+pub fn store_emission_record(
+    storage: &mut dyn Storage,
+    bucket: Bucket,
+    record: &EmissionRecord,
+) -> StdResult<()> {
+    EMISSION_HISTORY.save(
+        storage,
+        (&bucket.to_string(), record.month_index.u128()),
+        record,
+    )
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_emission_history(
+    storage: &dyn Storage,
+    bucket: Bucket,
+    start_after: Option<Uint128>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EmissionRecord>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|month_index| Bound::exclusive(month_index.u128()));
+
+    EMISSION_HISTORY
+        .prefix(&bucket.to_string())
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect()
+}
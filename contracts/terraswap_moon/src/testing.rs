@@ -1,1682 +1,3684 @@
 use crate::contract::{
-    assert_max_spread, assert_minimum_assets, execute, instantiate, query_moon_info, query_pool,
-    query_reverse_simulation, query_simulation, reply,
+    do_emission, query_all_emission_status, query_burn_source, query_burn_stats, query_can_emit,
+    query_clsm_price, query_emission_history, query_emission_schedule, query_next_emission,
+    query_token_stats, query_total_burned, query_vesting_status,
 };
+use crate::contract::{execute, instantiate, query_moon_info};
 use crate::error::ContractError;
-use classic_bindings::TerraMsg;
+use crate::state::{store_emission_record, MOON_CONFIG};
+use crate::util::{
+    assert_allowance, transfer_token_message, transfer_token_message_with_memo, BURN_ADDRESS,
+};
 use classic_terraswap::mock_querier::mock_dependencies;
-use std::str::FromStr;
 
-use classic_terraswap::asset::{Asset, AssetInfo, MoonInfo};
+use classic_terraswap::asset::{Asset, AssetInfo, MoonInfo, VestInfo};
 use classic_terraswap::moon::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    AutomaticBurnResponse, Bucket, BurnSource, CanEmitResponse, Cw20HookMsg, EmissionRecord,
+    EmissionScheduleEntry, EmissionStatus, InstantiateMsg, MoonExecuteMsg, NextEmissionResponse,
+    PriceBand, TokenStatsResponse, TotalBurnedResponse, VestingStatus,
 };
-use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+use classic_terraswap::pair::{ExecuteMsg as PairExecuteMsg, PoolResponse as PairPoolResponse};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn, Response, StdError, SubMsg,
-    SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal, OverflowError,
+    OverflowOperation, StdError, SubMsg, Uint128, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom};
+
+fn vest_info(address: &str) -> VestInfo {
+    VestInfo {
+        address: address.to_string(),
+        monthly_amount: Uint128::from(100u128),
+        month_count: Uint128::from(12u128),
+        month_index: Uint128::zero(),
+        asset_info: None,
+    }
+}
+
+fn moon_instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        clsm_addr: "clsm0000".to_string(),
+        timer_trigger: "timer0000".to_string(),
+        lunc_recipient: "recipient0000".to_string(),
+        minter_addr: "minter0000".to_string(),
+        lunc_mint_ratio: Decimal::percent(200),
+        ustc_mint_ratio: Decimal::percent(50),
+        max_mint_ratio: None,
+        max_mint_amount: Uint128::from(1_000_000u128),
+        mint_price_band: None,
+        max_supply: Uint128::from(1_000_000_000_000u128),
+        allow_partial: None,
+        interval_seconds: None,
+        reserve_balance: None,
+        max_owed_months: None,
+        treasury_addr: "treasury0000".to_string(),
+        burn_threshold: Uint128::from(1_000_000_000u128),
+        high_burn_divisor: Uint128::from(4u128),
+        low_burn_divisor: Uint128::from(100u128),
+        burn_source: None,
+        pair_vest: vest_info("pair0000"),
+        nft_vest: vest_info("nft0000"),
+        marketing_vest: vest_info("marketing0000"),
+        game_vest: vest_info("game0000"),
+        team_vest: vest_info("team0000"),
+    }
+}
 
 #[test]
-fn proper_initialization() {
+fn test_query_moon_info() {
     let mut deps = mock_dependencies(&[]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
-
-    // we can just call .unwrap() to assert this was a success
-    let env = mock_env();
+    let mut msg = moon_instantiate_msg();
+    msg.game_vest.asset_info = Some(AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    });
     let info = mock_info("addr0000", &[]);
-    let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![SubMsg {
-            msg: WasmMsg::Instantiate {
-                code_id: 10u64,
-                msg: to_binary(&TokenInstantiateMsg {
-                    name: "terraswap liquidity token".to_string(),
-                    symbol: "uLP".to_string(),
-                    decimals: 6,
-                    initial_balances: vec![],
-                    mint: Some(MinterResponse {
-                        minter: MOCK_CONTRACT_ADDR.to_string(),
-                        cap: None,
-                    }),
-                })
-                .unwrap(),
-                funds: vec![],
-                label: "lp".to_string(),
-                admin: None,
-            }
-            .into(),
-            gas_limit: None,
-            id: 1,
-            reply_on: ReplyOn::Success,
-        }]
-    );
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
-
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    instantiate(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
 
-    // it worked, let's query the state
-    let moon_info: MoonInfo = query_moon_info(deps.as_ref()).unwrap();
-    assert_eq!("liquidity0000", moon_info.liquidity_token.as_str());
+    let moon_info = query_moon_info(deps.as_ref()).unwrap();
     assert_eq!(
-        moon_info.asset_infos,
-        [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string()
-            }
-        ]
+        moon_info,
+        MoonInfo {
+            clsm_addr: Addr::unchecked("clsm0000"),
+            authorized: vec![Addr::unchecked("timer0000")],
+            lunc_recipient: Addr::unchecked("recipient0000"),
+            minter_addr: Addr::unchecked("minter0000"),
+            lunc_mint_ratio: msg.lunc_mint_ratio,
+            ustc_mint_ratio: msg.ustc_mint_ratio,
+            max_mint_ratio: Decimal::from_ratio(10u128, 1u128),
+            max_mint_amount: msg.max_mint_amount,
+            mint_price_band: None,
+            max_supply: msg.max_supply,
+            total_minted: Uint128::zero(),
+            allow_partial: false,
+            interval_seconds: 2_592_000,
+            reserve_balance: Uint128::zero(),
+            max_owed_months: Uint128::from(6u128),
+            treasury_addr: Addr::unchecked("treasury0000"),
+            burn_threshold: msg.burn_threshold,
+            high_burn_divisor: msg.high_burn_divisor,
+            low_burn_divisor: msg.low_burn_divisor,
+            burn_source: BurnSource::ContractSelf,
+            paused: false,
+            pair_vest: msg.pair_vest,
+            nft_vest: msg.nft_vest,
+            marketing_vest: msg.marketing_vest,
+            game_vest: msg.game_vest,
+            team_vest: msg.team_vest,
+        }
     );
 }
 
 #[test]
-fn provide_liquidity() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(1_100u128),
-    }]);
+fn test_send_lunc() {
+    let mut deps = mock_dependencies(&[]);
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
-        ),
-        (&"asset0000".to_string(), &[]),
-    ]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
+    // only the timer trigger can send LUNC
+    let msg = MoonExecuteMsg::SendLunc {
+        amount: Uint128::from(1_000u128),
     };
-
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let msg = MoonExecuteMsg::SendLunc {
+        amount: Uint128::from(1_000u128),
     };
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "recipient0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(1_000u128),
+            }],
+        }))]
+    );
+}
+
+#[test]
+fn test_dynamic_mint() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"clsm0000".to_string(), &[])]);
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 1_000))]);
 
-    // should raise MinimumLiquidityAmountError with insufficient initial liquidity
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(1u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(1u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: None,
+    // LUNC mints with the LUNC ratio at the pool price
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
     };
-    let env = mock_env();
     let info = mock_info(
         "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(1u128),
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
         }],
     );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(1_000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "minter0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "pair0000".to_string(),
+                    amount: Uint128::from(2_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
 
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-
-    match res {
-        ContractError::MinimumLiquidityAmountError {
-            min_lp_token,
-            given_lp,
-        } => {
-            assert_eq!(min_lp_token, "1000");
-            assert_eq!(given_lp, "1");
-        }
-        _ => panic!("Must return MinimumLiquidityAmountError"),
-    }
-
-    // successfully provide liquidity for the exist pool
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(1_100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(1_100u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: None,
+    // the pair holds no USTC, so USTC mints with the USTC ratio only
+    let msg = MoonExecuteMsg::DynamicMintFromUstc {
+        amount: Uint128::from(1_000u128),
     };
-
-    let env = mock_env();
     let info = mock_info(
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(1_100u128),
+            amount: Uint128::from(1_000u128),
         }],
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let liquidity_to_contract_msg = res.messages.get(0).expect("no message");
-    let transfer_from_msg = res.messages.get(1).expect("no message");
-    let mint_msg = res.messages.get(2).expect("no message");
-
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        liquidity_to_contract_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "minter0000".to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: 1_000u128.into(),
+                recipient: "pair0000".to_string(),
+                amount: Uint128::from(500u128),
             })
             .unwrap(),
             funds: vec![],
         }))
     );
+
+    // the burned amount must be sent along
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
-        transfer_from_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: "addr0000".to_string(),
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(1_100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        ))
     );
+
+    // zero amount mints nothing
+    let msg = MoonExecuteMsg::DynamicMintFromUstc {
+        amount: Uint128::zero(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
+}
+
+#[test]
+fn test_mint_and_provide() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"clsm0000".to_string(), &[])]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 1_000))]);
+
+    let msg = MoonExecuteMsg::MintAndProvide {
+        clsm_amount: Uint128::from(2_000u128),
+        pair_asset_amount: Uint128::from(500u128),
+    };
+
+    // only the timer trigger or the minter can seed the pair
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the mint lands before the pair pulls the CLSM
+    let info = mock_info("minter0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        mint_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "minter0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(2_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: "pair0000".to_string(),
+                    amount: Uint128::from(2_000u128),
+                    expires: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0000".to_string(),
+                msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                    assets: [
+                        Asset {
+                            info: AssetInfo::Token {
+                                contract_addr: "clsm0000".to_string(),
+                            },
+                            amount: Uint128::from(2_000u128),
+                        },
+                        Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: "uluna".to_string(),
+                            },
+                            amount: Uint128::from(500u128),
+                        },
+                    ],
+                    receiver: None,
+                    deadline: None,
+                    slippage_tolerance: None,
+                    min_lp_tokens: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(500u128),
+                }],
+            })),
+        ]
     );
 
-    // providing liquidity with a ratio exceeding the specified slippage tolerance
-    // should return MaxSlippageAssertion
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                200u128 + 200u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.total_minted, Uint128::from(2_000u128));
+}
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
-        ),
-    ]);
+#[test]
+fn test_update_mint_ratios() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"clsm0000".to_string(), &[])]);
 
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(200u128),
-            },
-        ],
-        receiver: Some("staking0000".to_string()), // try changing receiver
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.005").unwrap()),
+    // the ratios are checked against the maximum from the start
+    let mut msg = moon_instantiate_msg();
+    msg.max_mint_ratio = Some(Decimal::one());
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidMintRatio {
+            ratio: Decimal::percent(200),
+            max: Decimal::one(),
+        }
+    );
+
+    let mut msg = moon_instantiate_msg();
+    msg.max_mint_ratio = Some(Decimal::percent(300));
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 1_000))]);
+
+    let msg = MoonExecuteMsg::UpdateMintRatios {
+        lunc_mint_ratio: Decimal::percent(50),
+        ustc_mint_ratio: Decimal::percent(50),
     };
 
-    let env = mock_env();
+    // only the timer trigger can update the ratios
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_mint_ratios"),
+            attr("lunc_mint_ratio", "0.5"),
+            attr("ustc_mint_ratio", "0.5"),
+        ]
+    );
+
+    // half of the pool value of the burned LUNC is minted
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
+    };
     let info = mock_info(
         "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(200u128),
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
         }],
     );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[3], attr("mint_amount", "500"));
 
-    match execute(deps.as_mut(), env, info, msg).unwrap_err() {
-        ContractError::MaxSlippageAssertion { .. } => (),
-        _ => panic!("MaxSlippageAssertion should be raised"),
-    }
+    // a ratio above the maximum is rejected
+    let msg = MoonExecuteMsg::UpdateMintRatios {
+        lunc_mint_ratio: Decimal::percent(50),
+        ustc_mint_ratio: Decimal::percent(301),
+    };
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidMintRatio {
+            ratio: Decimal::percent(301),
+            max: Decimal::percent(300),
+        }
+    );
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.ustc_mint_ratio, Decimal::percent(50));
+}
 
-    // providing liquidity at a rate that is not equal to the existing one
-    // should refund the remained amount of one side's assets
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                100u128 + 200u128, /* user deposit must be pre-applied */
-            ),
-        }],
+#[test]
+fn test_dynamic_mint_supply_cap() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&"holder0000".to_string(), &Uint128::from(1_000u128))],
     )]);
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
-        ),
-    ]);
+    // 1_000 supply and 2_000 minted reach the cap exactly
+    let mut msg = moon_instantiate_msg();
+    msg.max_supply = Uint128::from(3_000u128);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 1_000))]);
 
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-        ],
-        receiver: Some("staking0000".to_string()), // try changing receiver
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.05").unwrap()),
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
     };
-
-    let env = mock_env();
     let info = mock_info(
         "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128),
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
         }],
     );
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.attributes[3], attr("mint_amount", "2000"));
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.total_minted, Uint128::from(2_000u128));
 
-    let res: Response<TerraMsg> = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
-
+    // one more CLSM in circulation breaches the cap
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&"holder0000".to_string(), &Uint128::from(1_001u128))],
+    )]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
-        transfer_from_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: "addr0000".to_string(),
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        err,
+        ContractError::SupplyCapExceeded {
+            cap: Uint128::from(3_000u128),
+            would_be: Uint128::from(3_001u128),
+        }
+    );
+}
+
+#[test]
+fn test_dynamic_mint_pool_price() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"clsm0000".to_string(), &[])]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // 1 LUNC trades for 3 CLSM, minted at the 200% LUNC ratio
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(3_000, 1_000))]);
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
     );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        mint_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "staking0000".to_string(), // LP tokens sent to specified receiver
-                amount: Uint128::from(50u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res.attributes,
+        vec![
+            attr("action", "dynamic_mint"),
+            attr("denom", "uluna"),
+            attr("burn_amount", "1000"),
+            attr("mint_amount", "6000"),
+        ]
     );
 
-    // check wrong argument
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(50u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.005").unwrap()),
+    // 4 LUNC trade for 1 CLSM
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 4_000))]);
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
     };
-
-    let env = mock_env();
     let info = mock_info(
         "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128),
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
         }],
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Std(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            "Native token balance mismatch between the argument and the transferred".to_string()
-        ),
-        _ => panic!("Must return generic error"),
-    }
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[3], attr("mint_amount", "500"));
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128 + 98u128 /* user deposit must be pre-applied */),
-        }],
-    )]);
-
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-    ]);
-
-    // successfully provide liquidity, and refund remain asset
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(98u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.05").unwrap()),
+    // the mint is capped by max_mint_amount
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(10_000_000u128),
     };
-
-    let env = mock_env();
     let info = mock_info(
-        "addr0001",
+        "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(98u128),
+            denom: "uluna".to_string(),
+            amount: Uint128::from(10_000_000u128),
         }],
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
-    assert_eq!(
-        transfer_from_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: "addr0001".to_string(),
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(98u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
-    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[3], attr("mint_amount", "1000000"));
     assert_eq!(
-        mint_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "minter0000".to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0001".to_string(), // LP tokens sent to specified receiver
-                amount: Uint128::from(98u128),
+                recipient: "pair0000".to_string(),
+                amount: Uint128::from(1_000_000u128),
             })
             .unwrap(),
             funds: vec![],
         }))
     );
+
+    // a mint too small to be priced fails
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1u128),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
 }
 
 #[test]
-fn withdraw_liquidity() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(100u128),
-    }]);
+fn test_dynamic_mint_price_band() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"clsm0000".to_string(), &[])]);
+
+    // a band with its minimum above its maximum is rejected
+    let mut msg = moon_instantiate_msg();
+    msg.mint_price_band = Some(PriceBand {
+        min: Decimal::percent(400),
+        max: Decimal::percent(200),
+    });
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPriceBand {});
+
+    // 1 LUNC is worth between 2 and 4 CLSM
+    let mut msg = moon_instantiate_msg();
+    msg.mint_price_band = Some(PriceBand {
+        min: Decimal::percent(200),
+        max: Decimal::percent(400),
+    });
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    deps.querier.with_tax(
-        Decimal::zero(),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    let mint = || MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
     );
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-    ]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(3_000, 1_000))]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), mint()).unwrap();
+    assert_eq!(res.attributes[3], attr("mint_amount", "6000"));
+
+    // a pool skewed out of the band can't price the mint
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(9_000, 1_000))]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), mint()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PriceOutOfBand {
+            price: Decimal::percent(900),
+            min: Decimal::percent(200),
+            max: Decimal::percent(400),
+        }
+    );
+
+    // only an authorized caller can move the band
+    let msg = MoonExecuteMsg::UpdateMintPriceBand { band: None };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // without a band any pool price is accepted
+    let res = execute(deps.as_mut(), mock_env(), mock_info("timer0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "update_mint_price_band")]
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, mint()).unwrap();
+    assert_eq!(res.attributes[3], attr("mint_amount", "18000"));
+}
+
+#[test]
+fn test_query_vesting_status() {
+    let mut deps = mock_dependencies(&[]);
 
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.team_vest.month_index = Uint128::from(4u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
 
-    // withdraw liquidity
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: None,
-            deadline: None,
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
+    let res = query_vesting_status(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.pair,
+        VestingStatus {
+            month_index: Uint128::zero(),
+            month_count: Uint128::from(12u128),
+            monthly_amount: Uint128::from(100u128),
+            remaining: Uint128::from(1_200u128),
+            shortfall: Uint128::zero(),
+            months_owed: Uint128::zero(),
+            funding: Uint128::zero(),
+        }
+    );
+    assert_eq!(
+        res.team,
+        VestingStatus {
+            month_index: Uint128::from(4u128),
+            month_count: Uint128::from(12u128),
+            monthly_amount: Uint128::from(100u128),
+            remaining: Uint128::from(800u128),
+            shortfall: Uint128::zero(),
+            months_owed: Uint128::zero(),
+            funding: Uint128::zero(),
+        }
+    );
+}
 
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let log_withdrawn_share = res.attributes.get(2).expect("no log");
-    let log_refund_assets = res.attributes.get(3).expect("no log");
-    let msg_refund_0 = res.messages.get(0).expect("no message");
-    let msg_refund_1 = res.messages.get(1).expect("no message");
-    let msg_burn_liquidity = res.messages.get(2).expect("no message");
-    assert_eq!(
-        msg_refund_0,
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(100u128),
-            }],
-        }))
+#[test]
+fn test_query_clsm_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // 4_000 LUNC against 1_000 CLSM
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 4_000))]);
+    let res = query_clsm_price(deps.as_ref()).unwrap();
+    assert_eq!(res.price, Decimal::from_ratio(4u128, 1u128));
+    assert_eq!(
+        res.quote_asset_info,
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        }
     );
+
+    // an empty pair has no price
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(0, 0))]);
+    let err = query_clsm_price(deps.as_ref()).unwrap_err();
+    assert_eq!(err, ContractError::NoLiquidity {});
+}
+
+#[test]
+fn test_query_emission_schedule() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.team_vest.month_index = Uint128::from(2u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    // the schedule runs from the current month to the last one
+    let res = query_emission_schedule(deps.as_ref(), Bucket::Team, 100).unwrap();
+    assert_eq!(res.entries.len(), 10);
     assert_eq!(
-        msg_refund_1,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res.entries[0],
+        EmissionScheduleEntry {
+            month_index: Uint128::from(2u128),
+            amount: Uint128::from(100u128),
+        }
+    );
+    assert_eq!(
+        res.entries[9],
+        EmissionScheduleEntry {
+            month_index: Uint128::from(11u128),
+            amount: Uint128::from(100u128),
+        }
     );
+
+    // capped at max_entries
+    let res = query_emission_schedule(deps.as_ref(), Bucket::Team, 3).unwrap();
+    assert_eq!(res.entries.len(), 3);
+    assert_eq!(res.entries[2].month_index, Uint128::from(4u128));
+
+    // the state is left untouched
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.team_vest.month_index, Uint128::from(2u128));
+}
+
+#[test]
+fn test_holder_burn() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let burn_msg = |amount: u128| {
+        MoonExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "holder0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Burn {}).unwrap(),
+        })
+    };
+
+    // only CLSM can be burned
+    let info = mock_info("asset0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, burn_msg(100)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("clsm0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, burn_msg(100)).unwrap();
     assert_eq!(
-        msg_burn_liquidity,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Burn {
                 amount: Uint128::from(100u128),
             })
             .unwrap(),
             funds: vec![],
-        }))
+        }))]
     );
+    assert!(res.attributes.contains(&attr("sender", "holder0000")));
 
-    assert_eq!(
-        log_withdrawn_share,
-        &attr("withdrawn_share", 100u128.to_string())
-    );
-    assert_eq!(
-        log_refund_assets,
-        &attr("refund_assets", "100uusd, 100asset0000")
-    );
+    // the tally adds up every burn
+    let info = mock_info("clsm0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, burn_msg(250)).unwrap();
+    let res = query_burn_stats(deps.as_ref()).unwrap();
+    assert_eq!(res.total_burned, Uint128::from(350u128));
+}
 
-    // withdraw liquidity with assert min_assets
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: Some([
-                Asset {
-                    info: AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                    amount: Uint128::from(1000u128),
-                },
-                Asset {
-                    info: AssetInfo::Token {
-                        contract_addr: "asset0000".to_string(),
-                    },
-                    amount: Uint128::zero(),
-                },
-            ]),
-            deadline: None,
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
+#[test]
+fn test_query_total_burned() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            ),
+            (&"holder0000".to_string(), &Uint128::from(2_000_000_000u128)),
+        ],
+    )]);
 
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
+    let res = query_total_burned(deps.as_ref()).unwrap();
     assert_eq!(
         res,
-        ContractError::MinAmountAssertion {
-            min_asset: "1000uusd".to_string(),
-            asset: "100uusd".to_string()
+        TotalBurnedResponse {
+            total_burned: Uint128::zero(),
+            automatic_burned: Uint128::zero(),
+            voluntary_burned: Uint128::zero(),
         }
     );
 
-    // failed to withdraw liquidity due to deadline
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: None,
-            deadline: Some(100u64),
-        })
-        .unwrap(),
+    // the automatic burn only adds to the automatic tally
+    let info = mock_info("timer0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    let res = query_total_burned(deps.as_ref()).unwrap();
+    assert_eq!(res.automatic_burned, Uint128::from(500_000_000u128));
+    assert_eq!(res.voluntary_burned, Uint128::zero());
+
+    // and a holder burn only to the voluntary one
+    let info = mock_info("clsm0000", &[]);
+    let msg = MoonExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "holder0000".to_string(),
         amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Burn {}).unwrap(),
     });
-
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query_total_burned(deps.as_ref()).unwrap();
     assert_eq!(
-        err,
-        ContractError::Std(StdError::generic_err("Expired deadline"))
-    )
+        res,
+        TotalBurnedResponse {
+            total_burned: Uint128::from(500_000_100u128),
+            automatic_burned: Uint128::from(500_000_000u128),
+            voluntary_burned: Uint128::from(100u128),
+        }
+    );
 }
 
 #[test]
-fn failed_reply_with_unknown_id() {
+fn test_verify_minter() {
     let mut deps = mock_dependencies(&[]);
 
-    let res = reply(
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // the configured minter mints the CLSM
+    deps.querier
+        .with_token_minters(&[(&"clsm0000".to_string(), &"minter0000".to_string())]);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
         deps.as_mut(),
         mock_env(),
-        Reply {
-            id: 9,
-            result: SubMsgResult::Ok(SubMsgResponse {
-                events: vec![],
-                data: Some(vec![].into()),
-            }),
-        },
+        info,
+        MoonExecuteMsg::VerifyMinter {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "verify_minter"),
+            attr("minter", "minter0000")
+        ]
     );
 
-    assert_eq!(res, Err(StdError::generic_err("invalid reply msg")))
+    // any other minter is a misconfiguration, the contract itself included
+    // as the mint messages go to the configured minter
+    for minter in ["minter0001", MOCK_CONTRACT_ADDR] {
+        deps.querier
+            .with_token_minters(&[(&"clsm0000".to_string(), &minter.to_string())]);
+        let info = mock_info("addr0000", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            MoonExecuteMsg::VerifyMinter {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MinterMismatch {
+                expected: "minter0000".to_string(),
+                actual: minter.to_string(),
+            }
+        );
+    }
 }
 
 #[test]
-fn try_native_to_token() {
-    let total_share = Uint128::from(30000000000u128);
-    let asset_pool_amount = Uint128::from(20000000000u128);
-    let collateral_pool_amount = Uint128::from(30000000000u128);
-    let exchange_rate: Decimal = Decimal::from_ratio(asset_pool_amount, collateral_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
+fn test_emission_recipient_override() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
 
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
-    }]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the timer trigger can redirect the emissions
+    let info = mock_info("addr0000", &[]);
+    let msg = MoonExecuteMsg::VestingMint {
+        to: Some("cold0000".to_string()),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
+        to: Some("cold0000".to_string()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "cold0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
 
-    deps.querier.with_tax(
-        Decimal::zero(),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    // the month moves on but the configured recipient is kept
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.team_vest.month_index, Uint128::from(1u128));
+    assert_eq!(
+        moon_config.team_vest.address,
+        deps.api.addr_canonicalize("team0000").unwrap()
     );
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
-        ),
-    ]);
+    let res = query_emission_history(deps.as_ref(), Bucket::Team, None, None).unwrap();
+    assert_eq!(res.records[0].recipient, Addr::unchecked("cold0000"));
+}
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+#[test]
+fn test_emission_history() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
 
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let env = mock_env();
+    let mut next_env = env.clone();
+    next_env.block.time = env.block.time.plus_seconds(2_592_000);
+    for env in [env.clone(), next_env.clone()] {
+        let info = mock_info("timer0000", &[]);
+        let msg = MoonExecuteMsg::Emit {
+            bucket: Bucket::Team,
+            to: None,
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+    }
 
-    // normal swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
+    // each emission leaves a record
+    let res = query_emission_history(deps.as_ref(), Bucket::Team, None, None).unwrap();
+    assert_eq!(
+        res.records,
+        vec![
+            EmissionRecord {
+                month_index: Uint128::from(1u128),
+                timestamp: env.block.time.seconds(),
+                amount: Uint128::from(100u128),
+                recipient: Addr::unchecked("team0000"),
             },
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-        deadline: None,
-    };
-    let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: offer_amount,
-        }],
+            EmissionRecord {
+                month_index: Uint128::from(2u128),
+                timestamp: next_env.block.time.seconds(),
+                amount: Uint128::from(100u128),
+                recipient: Addr::unchecked("team0000"),
+            },
+        ]
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
 
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount =
-        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
+    // paginated by month index
+    let res = query_emission_history(
+        deps.as_ref(),
+        Bucket::Team,
+        Some(Uint128::from(1u128)),
+        Some(1),
+    )
+    .unwrap();
+    assert_eq!(res.records.len(), 1);
+    assert_eq!(res.records[0].month_index, Uint128::from(2u128));
+
+    // other buckets keep their own history
+    let res = query_emission_history(deps.as_ref(), Bucket::Marketing, None, None).unwrap();
+    assert!(res.records.is_empty());
+}
+
+#[test]
+fn test_emission_history_pagination() {
+    let mut deps = mock_dependencies(&[]);
+
+    for month_index in 1..=50u128 {
+        store_emission_record(
+            deps.as_mut().storage,
+            Bucket::Game,
+            &EmissionRecord {
+                month_index: Uint128::from(month_index),
+                timestamp: 0,
+                amount: Uint128::from(100u128),
+                recipient: Addr::unchecked("game0000"),
+            },
+        )
         .unwrap();
-    let expected_tax_amount = Uint128::zero(); // no tax for token
+    }
 
-    // check simulation res
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: collateral_pool_amount, /* user deposit must be pre-applied */
-        }],
-    )]);
+    // a limit above the maximum is clamped to 30 records
+    let res = query_emission_history(deps.as_ref(), Bucket::Game, None, Some(100)).unwrap();
+    assert_eq!(res.records.len(), 30);
+    assert_eq!(res.records[0].month_index, Uint128::from(1u128));
+    assert_eq!(res.records[29].month_index, Uint128::from(30u128));
 
-    let simulation_res: SimulationResponse = query_simulation(
+    // the next page starts after the last record read
+    let res = query_emission_history(
         deps.as_ref(),
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: offer_amount,
-        },
+        Bucket::Game,
+        Some(res.records[29].month_index),
+        Some(100),
     )
     .unwrap();
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+    assert_eq!(res.records.len(), 20);
+    assert_eq!(res.records[0].month_index, Uint128::from(31u128));
+    assert_eq!(res.records[19].month_index, Uint128::from(50u128));
 
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+    let res = query_emission_history(
         deps.as_ref(),
-        Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            amount: expected_return_amount,
-        },
+        Bucket::Game,
+        Some(Uint128::from(50u128)),
+        None,
     )
     .unwrap();
+    assert!(res.records.is_empty());
+}
 
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
+#[test]
+fn test_weekly_emission_interval() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "swap"),
-            attr("sender", "addr0000"),
-            attr("receiver", "addr0000"),
-            attr("offer_asset", "uusd"),
-            attr("ask_asset", "asset0000"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("tax_amount", expected_tax_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
-        ]
-    );
+    // a zero interval would let every block emit
+    let mut msg = moon_instantiate_msg();
+    msg.interval_seconds = Some(0);
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidEmissionInterval {});
+
+    let mut msg = moon_instantiate_msg();
+    msg.interval_seconds = Some(604_800);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    let env = mock_env();
+    let env_after = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env
+    };
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
+        to: None,
+    };
+    let info = mock_info("timer0000", &[]);
+    execute(deps.as_mut(), env_after(0), info.clone(), msg.clone()).unwrap();
+
+    // six days later the week has not passed
+    let err = execute(deps.as_mut(), env_after(518_400), info.clone(), msg.clone()).unwrap_err();
     assert_eq!(
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: expected_return_amount,
-            })
-            .unwrap(),
-            funds: vec![],
-        })),
-        msg_transfer,
+        err,
+        ContractError::EmissionTooSoon {
+            next_available: env.block.time.seconds() + 604_800,
+        }
     );
+
+    // a week later the next emission runs, long before a monthly one would
+    execute(deps.as_mut(), env_after(604_800), info, msg).unwrap();
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.interval_seconds, 604_800);
+    assert_eq!(moon_config.team_vest.month_index, Uint128::from(2u128));
 }
 
 #[test]
-fn try_token_to_native() {
-    let total_share = Uint128::from(20000000000u128);
-    let asset_pool_amount = Uint128::from(30000000000u128);
-    let collateral_pool_amount = Uint128::from(20000000000u128);
-    let exchange_rate = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
-
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: collateral_pool_amount,
-    }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(
-                &MOCK_CONTRACT_ADDR.to_string(),
-                &(asset_pool_amount + offer_amount),
-            )],
-        ),
-    ]);
-
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [8u8, 8u8],
-    };
+fn test_emission_interval_overflow() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
 
-    let env = mock_env();
+    let mut msg = moon_instantiate_msg();
+    msg.interval_seconds = Some(u64::MAX);
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    // a bucket which already emitted can't add the interval to its last emission
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.team_vest.last_emission_time = 1;
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+    let overflow =
+        ContractError::OverflowError(OverflowError::new(OverflowOperation::Add, 1u64, u64::MAX));
 
-    // unauthorized access; can not execute swap directly for token swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
         to: None,
-        deadline: None,
     };
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    // normal sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
-            deadline: None,
-        })
-        .unwrap(),
-    });
-    let env = mock_env();
-    let info = mock_info("asset0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, overflow);
 
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
-
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount =
-        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
-        .unwrap();
-    let expected_tax_amount = std::cmp::min(
-        Uint128::from(1000000u128),
-        expected_return_amount
-            .checked_sub(
-                expected_return_amount
-                    .multiply_ratio(Uint128::from(100u128), Uint128::from(101u128)),
-            )
-            .unwrap(),
-    );
-    // check simulation res
-    // return asset token balance as normal
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
-        ),
-    ]);
+    let err = query_next_emission(deps.as_ref(), mock_env(), Bucket::Team).unwrap_err();
+    assert_eq!(err, overflow);
+}
 
-    let simulation_res: SimulationResponse = query_simulation(
-        deps.as_ref(),
-        Asset {
-            amount: offer_amount,
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        },
-    )
-    .unwrap();
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+#[test]
+fn test_emission_reserve_balance() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_150u128))],
+    )]);
 
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
-        deps.as_ref(),
-        Asset {
-            amount: expected_return_amount,
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-        },
-    )
-    .unwrap();
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
+    let mut msg = moon_instantiate_msg();
+    msg.reserve_balance = Some(Uint128::from(1_000u128));
+    msg.team_vest.monthly_amount = Uint128::from(200u128);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
+    // the team month would leave 950 CLSM, below the reserve
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
+        to: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "swap"),
-            attr("sender", "addr0000"),
-            attr("receiver", "addr0000"),
-            attr("offer_asset", "asset0000"),
-            attr("ask_asset", "uusd"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("tax_amount", expected_tax_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
-        ]
+        err,
+        ContractError::ReserveFloorViolation {
+            reserve: Uint128::from(1_000u128),
+            remaining: Uint128::from(950u128),
+        }
     );
 
+    // the nft month leaves 1050 CLSM
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Nft,
+        to: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: expected_return_amount
-                    .checked_sub(expected_tax_amount)
-                    .unwrap(),
-            }],
-        })),
-        msg_transfer,
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "nft0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
     );
 
-    // failed due to non asset token contract try to execute sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
-            deadline: None,
-        })
-        .unwrap(),
-    });
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.team_vest.month_index, Uint128::zero());
+    assert_eq!(moon_config.nft_vest.month_index, Uint128::from(1u128));
 }
 
 #[test]
-fn test_max_spread() {
-    let offer_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
-    let ask_asset_info = AssetInfo::NativeToken {
-        denom: "ask_asset_info".to_string(),
-    };
+fn test_vesting_mint_unauthorized() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::zero(),
-        6u8,
-        6u8,
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // the emissions of every bucket are as restricted as a single one
+    let info = mock_info("addr0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(990000u128),
-        },
-        Uint128::zero(),
-        6u8,
-        6u8,
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.pair_vest.month_index, Uint128::zero());
+}
+
+#[test]
+fn test_emission_interval() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let env = mock_env();
+    let info = mock_info("timer0000", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap();
 
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::zero(),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::from(10001u128),
-        6u8,
-        6u8,
+    // a second emission within the interval fails
+    let mut next_env = env.clone();
+    next_env.block.time = env.block.time.plus_seconds(86_400);
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        next_env,
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::EmissionTooSoon {
+            next_available: env.block.time.seconds() + 2_592_000,
+        }
+    );
 
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::zero(),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(990000u128),
-        },
-        Uint128::from(10000u128),
-        6u8,
-        6u8,
+    // and succeeds once the interval has passed
+    let mut next_env = env.clone();
+    next_env.block.time = env.block.time.plus_seconds(2_592_000);
+    let info = mock_info("timer0000", &[]);
+    execute(
+        deps.as_mut(),
+        next_env,
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap();
+
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.pair_vest.month_index, Uint128::from(2u128));
+    assert_eq!(
+        moon_config.team_vest.last_emission_time,
+        env.block.time.seconds() + 2_592_000
+    );
 }
 
 #[test]
-fn test_max_spread_with_diff_decimal() {
-    let token_addr = "ask_asset_info".to_string();
-
+fn test_vesting_mint_from_pair() {
     let mut deps = mock_dependencies(&[]);
     deps.querier.with_token_balances(&[(
-        &token_addr,
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(10000000000u64),
-        )],
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
     )]);
-    let offer_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
-    let ask_asset_info = AssetInfo::Token {
-        contract_addr: token_addr.to_string(),
-    };
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(100000000u128),
-        },
-        Uint128::zero(),
-        6u8,
-        8u8,
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let env = mock_env();
+    let info = mock_info("pair0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap();
+    assert_eq!(res.messages.len(), 5);
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(98999999u128),
-        },
-        Uint128::zero(),
-        6u8,
-        8u8,
+    // the pair fires before the interval has passed, nothing is due
+    let mut next_env = env.clone();
+    next_env.block.time = env.block.time.plus_seconds(86_400);
+    let info = mock_info("pair0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        next_env,
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
-    .unwrap_err();
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
 
-    let offer_asset_info = AssetInfo::Token {
-        contract_addr: token_addr,
-    };
-    let ask_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.pair_vest.month_index, Uint128::from(1u128));
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(120000000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(1000000u128),
-        },
-        Uint128::zero(),
-        8u8,
-        6u8,
+    // only the buckets which are due are emitted
+    moon_config.team_vest.last_emission_time = 0;
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("pair0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap();
+    assert_eq!(res.messages.len(), 1);
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::from(120000000000u128),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::zero(),
-        8u8,
-        6u8,
+    // other callers still get the error
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
     )
     .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::EmissionTooSoon {
+            next_available: env.block.time.seconds() + 2_592_000,
+        }
+    );
 }
 
 #[test]
-fn test_deduct() {
+fn test_automatic_burn() {
     let mut deps = mock_dependencies(&[]);
 
-    let tax_rate = Decimal::percent(2);
-    let tax_cap = Uint128::from(1_000_000u128);
-    deps.querier.with_tax(
-        Decimal::percent(2),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the timer trigger can burn
+    let info = mock_info("addr0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // a quarter of the supply is burned above the threshold
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            ),
+            (&"holder0000".to_string(), &Uint128::from(2_000_000_000u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(500_000_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
     );
 
-    let amount = Uint128::from(1_000_000_000u128);
-    let expected_after_amount = std::cmp::max(
-        amount.checked_sub(amount * tax_rate).unwrap(),
-        amount.checked_sub(tax_cap).unwrap(),
+    // 1% of the supply is burned below the threshold
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128)),
+            (&"holder0000".to_string(), &Uint128::from(100_000u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(1_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
     );
 
-    let after_amount = (Asset {
-        info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
-        },
-        amount,
-    })
-    .deduct_tax(&deps.as_ref().querier)
+    // the burn is capped at the held balance
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128)),
+            (&"holder0000".to_string(), &Uint128::from(100_000u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
     .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(10u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
 
-    assert_eq!(expected_after_amount, after_amount.amount);
+    // nothing is held by the contract
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&"holder0000".to_string(), &Uint128::from(100_000u128))],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToBurn {});
 }
 
 #[test]
-fn test_query_pool() {
-    let total_share_amount = Uint128::from(111u128);
-    let asset_0_amount = Uint128::from(222u128);
-    let asset_1_amount = Uint128::from(333u128);
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: asset_0_amount,
-    }]);
+fn test_automatic_burn_from_pair() {
+    let mut deps = mock_dependencies(&[]);
 
-    deps.querier.with_token_balances(&[
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_1_amount)],
-        ),
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share_amount)],
-        ),
-    ]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
+    // the pair burns like the timer trigger
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128)),
+            (&"holder0000".to_string(), &Uint128::from(100_000u128)),
         ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+    )]);
+    let info = mock_info("pair0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // nothing to burn, the swap of the pair goes through
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&"holder0000".to_string(), &Uint128::from(100_000u128))],
+    )]);
+    let info = mock_info("pair0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "automatic_burn"),
+            attr("skipped", ContractError::NothingToBurn {}.to_string()),
+        ]
+    );
+
+    // paused
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::SetPaused { paused: true };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("pair0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    // the timer trigger still gets the error
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+}
+
+#[test]
+fn test_automatic_burn_receipt() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            ),
+            (&"holder0000".to_string(), &Uint128::from(2_000_000_000u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "automatic_burn"),
+            attr("total_supply", "2000000000"),
+            attr("burn_amount", "500000000"),
+            attr("branch", "high"),
+            attr("schema_version", "1"),
+        ]
+    );
+    assert_eq!(
+        res.data,
+        Some(
+            to_binary(&AutomaticBurnResponse {
+                burn_amount: Uint128::from(500_000_000u128),
+            })
+            .unwrap()
+        )
+    );
+}
+
+#[test]
+fn test_automatic_burn_source() {
+    for (burn_source, burn_msg) in [
+        (
+            None,
+            Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(500_000_000u128),
+            },
+        ),
+        (
+            Some(BurnSource::Pair),
+            Cw20ExecuteMsg::BurnFrom {
+                owner: "pair0000".to_string(),
+                amount: Uint128::from(500_000_000u128),
+            },
+        ),
+        (
+            Some(BurnSource::Custom(Addr::unchecked("burner0000"))),
+            Cw20ExecuteMsg::BurnFrom {
+                owner: "burner0000".to_string(),
+                amount: Uint128::from(500_000_000u128),
+            },
+        ),
+    ] {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_token_balances(&[(
+            &"clsm0000".to_string(),
+            &[
+                (
+                    &MOCK_CONTRACT_ADDR.to_string(),
+                    &Uint128::from(1_000_000_000u128),
+                ),
+                (&"pair0000".to_string(), &Uint128::from(1_000_000_000u128)),
+                (&"burner0000".to_string(), &Uint128::from(1_000_000_000u128)),
+            ],
+        )]);
+        deps.querier.with_token_allowances(&[(
+            &"clsm0000".to_string(),
+            &[
+                (
+                    &"pair0000".to_string(),
+                    &MOCK_CONTRACT_ADDR.to_string(),
+                    &Uint128::from(500_000_000u128),
+                ),
+                (
+                    &"burner0000".to_string(),
+                    &MOCK_CONTRACT_ADDR.to_string(),
+                    &Uint128::from(500_000_000u128),
+                ),
+            ],
+        )]);
+
+        let mut msg = moon_instantiate_msg();
+        msg.burn_source = burn_source;
+        let info = mock_info("addr0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("timer0000", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            MoonExecuteMsg::AutomaticBurn {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&burn_msg).unwrap(),
+                funds: vec![],
+            }))]
+        );
+    }
+}
+
+#[test]
+fn test_update_burn_source() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            ),
+            (&"pair0000".to_string(), &Uint128::from(1_000_000_000u128)),
+        ],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+    assert_eq!(
+        query_burn_source(deps.as_ref()).unwrap().burn_source,
+        BurnSource::ContractSelf
+    );
+
+    // only the timer trigger can update the burn source
+    let msg = MoonExecuteMsg::UpdateBurnSource {
+        burn_source: BurnSource::Pair,
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_burn_source"),
+            attr("burn_source", "pair"),
+        ]
+    );
+    assert_eq!(
+        query_burn_source(deps.as_ref()).unwrap().burn_source,
+        BurnSource::Pair
+    );
+
+    // the pair has not allowed the contract to burn its balance
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientAllowance {
+            needed: Uint128::from(250_000_000u128),
+            granted: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_automatic_burn_threshold_boundary() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // a supply equal to the threshold burns with the high divisor
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            ),
+            (&"holder0000".to_string(), &Uint128::from(1_000_000_000u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(250_000_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // one below the threshold burns with the low divisor
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000_000u128),
+            ),
+            (&"holder0000".to_string(), &Uint128::from(999_999_999u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(9_999_999u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // a zero divisor left in storage is rejected instead of panicking
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.low_burn_divisor = Uint128::zero();
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBurnDivisor {});
+}
+
+#[test]
+fn test_query_token_stats() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // above the threshold a quarter of the supply is due, capped by the balance
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128)),
+            (&"holder0000".to_string(), &Uint128::from(2_000_000_000u128)),
+        ],
+    )]);
+    assert_eq!(
+        query_token_stats(deps.as_ref(), mock_env()).unwrap(),
+        TokenStatsResponse {
+            total_supply: Uint128::from(2_000_000_000u128),
+            contract_balance: Uint128::from(1_000u128),
+            next_burn_amount: Uint128::from(1_000u128),
+        }
+    );
+
+    // below the threshold 1% of the supply is due
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(50_000u128)),
+            (&"holder0000".to_string(), &Uint128::from(100_000u128)),
+        ],
+    )]);
+    assert_eq!(
+        query_token_stats(deps.as_ref(), mock_env()).unwrap(),
+        TokenStatsResponse {
+            total_supply: Uint128::from(100_000u128),
+            contract_balance: Uint128::from(50_000u128),
+            next_burn_amount: Uint128::from(1_000u128),
+        }
+    );
+}
+
+#[test]
+fn test_sweep_completed_vesting() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(5_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.pair_vest.month_index = Uint128::from(12u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    // a bucket which is still vesting is not swept
+    let msg = MoonExecuteMsg::SweepCompletedVesting {
+        bucket: Bucket::Nft,
+    };
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::VestingNotCompleted {});
+
+    // the other four buckets still reserve 4 * 1_200
+    let msg = MoonExecuteMsg::SweepCompletedVesting {
+        bucket: Bucket::Pair,
+    };
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::from(200u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn test_emission_attributes() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 5);
+    assert_eq!(
+        res.attributes[..4],
+        [
+            attr("action", "emission"),
+            attr("bucket", "pair"),
+            attr("amount", "100"),
+            attr("month_index", "1"),
+        ]
+    );
+    assert_eq!(
+        res.attributes[16..20],
+        [
+            attr("action", "emission"),
+            attr("bucket", "team"),
+            attr("amount", "100"),
+            attr("month_index", "1"),
+        ]
+    );
+    // the schema version closes the response once
+    assert_eq!(res.attributes[20], attr("schema_version", "1"));
+}
+
+#[test]
+fn test_update_burn_params() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let msg = MoonExecuteMsg::UpdateBurnParams {
+        burn_threshold: Uint128::from(1_000u128),
+        high_burn_divisor: Uint128::from(2u128),
+        low_burn_divisor: Uint128::from(10u128),
+    };
+
+    // only the timer trigger can update the burn params
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // divisors can not be zero
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::UpdateBurnParams {
+            burn_threshold: Uint128::from(1_000u128),
+            high_burn_divisor: Uint128::zero(),
+            low_burn_divisor: Uint128::from(10u128),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBurnDivisor {});
+
+    let info = mock_info("timer0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // half of the supply is burned above the threshold
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(5_000u128)),
+            (&"holder0000".to_string(), &Uint128::from(2_000u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(1_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // 10% of the supply is burned below the threshold
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(5_000u128)),
+            (&"holder0000".to_string(), &Uint128::from(500u128)),
+        ],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AutomaticBurn {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn test_do_emission() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(150u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let env = mock_env();
+    let (message, attributes) = do_emission(deps.as_mut(), &env, Bucket::Game, None, None).unwrap();
+    assert_eq!(
+        message,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "game0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        attributes,
+        vec![
+            attr("action", "emission"),
+            attr("bucket", "game"),
+            attr("amount", "100"),
+            attr("month_index", "1"),
+        ]
+    );
+
+    // the moved schedule is saved before the transfer is dispatched
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.game_vest.month_index, Uint128::from(1u128));
+    assert_eq!(
+        moon_config.game_vest.last_emission_time,
+        env.block.time.seconds()
+    );
+    assert_eq!(moon_config.team_vest.month_index, Uint128::zero());
+
+    // only the balance left by another bucket is available
+    let err = do_emission(
+        deps.as_mut(),
+        &env,
+        Bucket::Team,
+        Some(Uint128::from(50u128)),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::LessThanVesting {});
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.team_vest.month_index, Uint128::zero());
+
+    // the emitted bucket waits for the next interval
+    let err = do_emission(deps.as_mut(), &env, Bucket::Game, None, None).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::EmissionTooSoon {
+            next_available: env.block.time.seconds() + 2_592_000,
+        }
+    );
+}
+
+#[test]
+fn test_emit_completed_bucket() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.team_vest.month_index = Uint128::from(12u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    // the timer trigger is authorized, the bucket has nothing left to vest
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
+        to: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::VestingComplete {
+            bucket: Bucket::Team
+        }
+    );
+
+    let res = query_can_emit(deps.as_ref(), mock_env(), Bucket::Team).unwrap();
+    assert!(!res.ok);
+    assert_eq!(
+        res.reason,
+        Some("Vesting of the team bucket is complete".to_string())
+    );
+}
+
+#[test]
+fn test_emit_bucket() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the timer trigger can emit
+    let info = mock_info("addr0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Pair,
+        to: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // every bucket routes through the same emission to its own recipient
+    for bucket in Bucket::all() {
+        let info = mock_info("timer0000", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            MoonExecuteMsg::Emit { bucket, to: None },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: format!("{}0000", bucket),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+        assert_eq!(res.attributes[1], attr("bucket", bucket.to_string()));
+
+        let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(moon_config.vest(bucket).month_index, Uint128::from(1u128));
+    }
+}
+
+#[test]
+fn test_emit_invalid_stored_address() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // a corrupted stored address names the field instead of a generic error
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.clsm_addr = CanonicalAddr::from(vec![1u8, 2, 3]);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Marketing,
+        to: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidStoredAddress {
+            field: "clsm_addr".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_emit_all() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(250u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the timer trigger can emit
+    let info = mock_info("addr0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.nft_vest.month_index = Uint128::from(12u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    // nft is completed, and the balance only covers two more buckets
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.attributes[0], attr("emitted_buckets", "pair,marketing"));
+
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.pair_vest.month_index, Uint128::from(1u128));
+    assert_eq!(moon_config.marketing_vest.month_index, Uint128::from(1u128));
+    assert_eq!(moon_config.game_vest.month_index, Uint128::zero());
+    assert_eq!(moon_config.team_vest.month_index, Uint128::zero());
+
+    assert_eq!(res.attributes[1], attr("owed_buckets", "game,team"));
+
+    // the underfunded buckets carried their month forward
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.game_vest.months_owed, Uint128::from(1u128));
+    assert_eq!(moon_config.team_vest.months_owed, Uint128::from(1u128));
+
+    // no bucket is due until the next interval
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToEmit {});
+}
+
+#[test]
+fn test_emit_all_reads_balance_once() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // the five buckets vest CLSM, so its balance is queried a single time
+    let queries = deps.querier.query_count();
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 5);
+    assert_eq!(deps.querier.query_count() - queries, 1);
+
+    // a bucket vesting another asset reads that balance once more
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::from(1_000u128),
+    }]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let mut msg = moon_instantiate_msg();
+    msg.game_vest.asset_info = Some(AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    });
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let queries = deps.querier.query_count();
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 5);
+    assert_eq!(deps.querier.query_count() - queries, 2);
+}
+
+#[test]
+fn test_update_vest_recipient() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let msg = MoonExecuteMsg::UpdateVestRecipient {
+        bucket: Bucket::Marketing,
+        new_address: "marketing0001".to_string(),
+    };
+
+    // only the timer trigger can update a recipient
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.marketing_vest.month_index = Uint128::from(3u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_vest_recipient"),
+            attr("bucket", "marketing"),
+            attr("old_address", "marketing0000"),
+            attr("new_address", "marketing0001"),
+        ]
+    );
+
+    // the schedule is left intact
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.marketing_vest.month_index, Uint128::from(3u128));
+    assert_eq!(
+        moon_config.marketing_vest.monthly_amount,
+        Uint128::from(100u128)
+    );
+    assert_eq!(
+        moon_config.nft_vest.address,
+        deps.api.addr_canonicalize("nft0000").unwrap()
+    );
+
+    // the next marketing emission goes to the new recipient
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    for bucket in [Bucket::Pair, Bucket::Nft, Bucket::Game, Bucket::Team] {
+        moon_config.vest_mut(bucket).month_index = Uint128::from(12u128);
+    }
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "marketing0001".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn test_skip_month() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let msg = MoonExecuteMsg::SkipMonth {
+        bucket: Bucket::Pair,
+    };
+
+    // only the timer trigger can skip a month
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skip_month"),
+            attr("bucket", "pair"),
+            attr("month_index", "1"),
+        ]
+    );
+    // nothing is transferred
+    assert!(res.messages.is_empty());
+
+    // the next emission is for the following month
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::Emit {
+            bucket: Bucket::Pair,
+            to: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes[..4],
+        vec![
+            attr("action", "emission"),
+            attr("bucket", "pair"),
+            attr("amount", "100"),
+            attr("month_index", "2"),
+        ]
+    );
+
+    // a completed bucket can't skip
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.pair_vest.month_index = Uint128::from(12u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::VestingComplete {
+            bucket: Bucket::Pair
+        }
+    );
+}
+
+#[test]
+fn test_emission_month_index_overflow() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // a corrupted schedule at the very end of the range
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.pair_vest.month_count = Uint128::MAX;
+    moon_config.pair_vest.month_index = Uint128::MAX - Uint128::from(1u8);
+    moon_config.pair_vest.months_owed = Uint128::MAX;
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Pair,
+        to: None,
+    };
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::OverflowError(OverflowError::new(
+            OverflowOperation::Add,
+            Uint128::MAX,
+            Uint128::from(1u8)
+        ))
+    );
+
+    // the last month of the range is still emitted, then the bucket is complete
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.pair_vest.months_owed = Uint128::zero();
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.pair_vest.month_index, Uint128::MAX);
+
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::VestingComplete {
+            bucket: Bucket::Pair
+        }
+    );
+}
+
+#[test]
+fn test_register_funding() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let msg = MoonExecuteMsg::RegisterFunding {
+        bucket: Bucket::Team,
+        amount: Uint128::from(900u128),
+    };
+
+    // only the timer trigger can register funding
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_funding"),
+            attr("bucket", "team"),
+            attr("amount", "900"),
+            attr("funding", "900"),
+        ]
+    );
+
+    // marketing owes months the balance could cover, but only the part not
+    // registered for the team is available to it
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.marketing_vest.months_owed = Uint128::from(2u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Marketing,
+        to: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[2], attr("amount", "100"));
+
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.marketing_vest.month_index, Uint128::from(1u128));
+    assert_eq!(moon_config.team_vest.funding, Uint128::from(900u128));
+
+    // the team draws on its own funding
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
+        to: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.team_vest.funding, Uint128::from(800u128));
+
+    // the registered funding can not exceed the balance
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::RegisterFunding {
+        bucket: Bucket::Marketing,
+        amount: Uint128::from(201u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::FundingExceedsBalance {});
+}
+
+#[test]
+fn test_partial_emission() {
+    for allow_partial in [false, true] {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.with_token_balances(&[(
+            &"clsm0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(50u128))],
+        )]);
+
+        let mut msg = moon_instantiate_msg();
+        msg.allow_partial = Some(allow_partial);
+        let info = mock_info("addr0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // only the marketing bucket is left to emit
+        let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+        for bucket in [Bucket::Pair, Bucket::Nft, Bucket::Game, Bucket::Team] {
+            moon_config.vest_mut(bucket).month_index = Uint128::from(12u128);
+        }
+        MOON_CONFIG
+            .save(deps.as_mut().storage, &moon_config)
+            .unwrap();
+
+        let info = mock_info("timer0000", &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            MoonExecuteMsg::EmitAll { to: None },
+        );
+        if !allow_partial {
+            // the strict mode waits for the full monthly amount
+            let res = res.unwrap();
+            assert!(res.messages.is_empty());
+            assert_eq!(res.attributes[1], attr("owed_buckets", "marketing"));
+            let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+            assert_eq!(moon_config.marketing_vest.month_index, Uint128::zero());
+            assert_eq!(moon_config.marketing_vest.months_owed, Uint128::from(1u128));
+            continue;
+        }
+
+        // the partial mode emits the half available and moves on
+        assert_eq!(
+            res.unwrap().messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "marketing0000".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+        let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(moon_config.marketing_vest.month_index, Uint128::from(1u128));
+        assert_eq!(moon_config.marketing_vest.shortfall, Uint128::from(50u128));
+    }
+}
+
+#[test]
+fn test_months_owed() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the marketing bucket is left to emit
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    for bucket in [Bucket::Pair, Bucket::Nft, Bucket::Game, Bucket::Team] {
+        moon_config.vest_mut(bucket).month_index = Uint128::from(12u128);
+    }
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    // two underfunded periods are carried forward
+    let mut env = mock_env();
+    for months_owed in [1u128, 2u128] {
+        let info = mock_info("timer0000", &[]);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            MoonExecuteMsg::EmitAll { to: None },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(res.attributes[1], attr("owed_buckets", "marketing"));
+
+        let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(moon_config.marketing_vest.month_index, Uint128::zero());
+        assert_eq!(
+            moon_config.marketing_vest.months_owed,
+            Uint128::from(months_owed)
+        );
+
+        env.block.time = env.block.time.plus_seconds(2_592_000);
+    }
+
+    // the funded call releases the owed months with the current one
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(300u128))],
+    )]);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "marketing0000".to_string(),
+                amount: Uint128::from(300u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(moon_config.marketing_vest.month_index, Uint128::from(3u128));
+    assert_eq!(moon_config.marketing_vest.months_owed, Uint128::zero());
+    assert_eq!(moon_config.marketing_vest.shortfall, Uint128::zero());
+}
+
+#[test]
+fn test_max_owed_months() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+    )]);
+
+    let mut msg = moon_instantiate_msg();
+    msg.max_owed_months = Some(Uint128::from(2u128));
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the marketing bucket is left to emit
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    for bucket in [Bucket::Pair, Bucket::Nft, Bucket::Game, Bucket::Team] {
+        moon_config.vest_mut(bucket).month_index = Uint128::from(12u128);
+    }
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    // the periods past the cap are not carried forward
+    let mut env = mock_env();
+    for period in 0..5u128 {
+        let info = mock_info("timer0000", &[]);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            MoonExecuteMsg::EmitAll { to: None },
+        );
+        if period < 2 {
+            res.unwrap();
+        } else {
+            assert_eq!(res.unwrap_err(), ContractError::NothingToEmit {});
+        }
+
+        let moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            moon_config.marketing_vest.months_owed,
+            Uint128::from((period + 1).min(2))
+        );
+
+        env.block.time = env.block.time.plus_seconds(2_592_000);
+    }
+}
+
+#[test]
+fn test_native_vesting_bucket() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::from(1_000u128),
+    }]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let mut msg = moon_instantiate_msg();
+    msg.game_vest.asset_info = Some(AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    });
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
+    )
+    .unwrap();
+
+    // the other buckets still vest CLSM
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "pair0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        res.messages[3],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "game0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))
+    );
+
+    // the native bucket is limited by the native balance
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.game_vest.monthly_amount = Uint128::from(2_000u128);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(2_592_000);
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        MoonExecuteMsg::EmitAll { to: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes[0],
+        attr("emitted_buckets", "pair,nft,marketing,team")
+    );
+}
+
+#[test]
+fn test_transfer_token_message_with_memo() {
+    let memo = to_binary(&"emission:marketing").unwrap();
+
+    // the memo turns the Cw20 transfer into a Send
+    let msg = transfer_token_message_with_memo(
+        Denom::Cw20(Addr::unchecked("clsm0000")),
+        Uint128::from(100u128),
+        Addr::unchecked("marketing0000"),
+        Some(memo.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "marketing0000".to_string(),
+                amount: Uint128::from(100u128),
+                msg: memo,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // without a memo it stays a plain transfer
+    let msg = transfer_token_message_with_memo(
+        Denom::Cw20(Addr::unchecked("clsm0000")),
+        Uint128::from(100u128),
+        Addr::unchecked("marketing0000"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "marketing0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_transfer_token_message_to_burn_address() {
+    // Cw20 tokens sent to the burn address are burned
+    let msg = transfer_token_message(
+        Denom::Cw20(Addr::unchecked("clsm0000")),
+        Uint128::from(100u128),
+        Addr::unchecked(BURN_ADDRESS),
+    )
+    .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // native tokens are burned by the send to the burn address itself
+    let msg = transfer_token_message(
+        Denom::Native("uluna".to_string()),
+        Uint128::from(100u128),
+        Addr::unchecked(BURN_ADDRESS),
+    )
+    .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: BURN_ADDRESS.to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        })
+    );
+}
+
+#[test]
+fn test_assert_allowance() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_allowances(&[(
+        &"clsm0000".to_string(),
+        &[(
+            &"pair0000".to_string(),
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(100u128),
+        )],
+    )]);
+
+    let querier = deps.as_ref().querier;
+    let err = assert_allowance(
+        &querier,
+        Addr::unchecked("clsm0000"),
+        Addr::unchecked("pair0000"),
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        Uint128::from(101u128),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientAllowance {
+            needed: Uint128::from(101u128),
+            granted: Uint128::from(100u128),
+        }
+    );
+
+    // no allowance was granted by this owner
+    let err = assert_allowance(
+        &querier,
+        Addr::unchecked("clsm0000"),
+        Addr::unchecked("team0000"),
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        Uint128::from(1u128),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientAllowance {
+            needed: Uint128::from(1u128),
+            granted: Uint128::zero(),
+        }
+    );
+
+    assert_allowance(
+        &querier,
+        Addr::unchecked("clsm0000"),
+        Addr::unchecked("pair0000"),
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        Uint128::from(100u128),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_emergency_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let msg = MoonExecuteMsg::EmergencyWithdraw {
+        amount: Uint128::from(5_000u128),
+        to: "safe0000".to_string(),
+    };
+
+    // the escape hatch only opens while paused
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::NotPaused {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        MoonExecuteMsg::SetPaused { paused: true },
+    )
+    .unwrap();
+
+    // only the timer trigger can withdraw
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the withdrawal is capped by the balance
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "safe0000".to_string(),
+                amount: Uint128::from(1_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "emergency_withdraw"),
+            attr("emergency", "true"),
+            attr("to", "safe0000"),
+            attr("amount", "1000"),
+        ]
+    );
+}
+
+#[test]
+fn test_set_paused() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the timer trigger can pause
+    let info = mock_info("addr0000", &[]);
+    let msg = MoonExecuteMsg::SetPaused { paused: true };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::SetPaused { paused: true };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    // queries are still available
+    query_vesting_status(deps.as_ref()).unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::SetPaused { paused: false };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::VestingMint { to: None },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 5);
+}
+
+#[test]
+fn test_update_timer_trigger() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // only the current timer trigger can rotate it
+    let info = mock_info("addr0000", &[]);
+    let msg = MoonExecuteMsg::UpdateTimerTrigger {
+        new_trigger: "timer0001".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_timer_trigger"),
+            attr("timer_trigger", "timer0000"),
+            attr("pending_timer_trigger", "timer0001"),
+        ]
+    );
+
+    // the old timer trigger keeps control until the proposal is accepted
+    let info = mock_info("timer0001", &[]);
+    let msg = MoonExecuteMsg::SetPaused { paused: true };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the proposed address can accept
+    let info = mock_info("timer0002", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AcceptTimerTrigger {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AcceptTimerTrigger {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "accept_timer_trigger"),
+            attr("old_timer_trigger", "timer0000"),
+            attr("new_timer_trigger", "timer0001"),
+        ]
+    );
+
+    // the old timer trigger is rejected and the new one is authorized
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::SetPaused { paused: false };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0001", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the proposal is consumed
+    let info = mock_info("timer0001", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AcceptTimerTrigger {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_update_timer_trigger_revoked() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::AddAuthorized {
+        address: "timer0001".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("timer0001", &[]);
+    let msg = MoonExecuteMsg::UpdateTimerTrigger {
+        new_trigger: "timer0002".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // revoking the proposer drops its pending proposal
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::RemoveAuthorized {
+        address: "timer0001".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("timer0002", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::AcceptTimerTrigger {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_update_minter_addr() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"clsm0000".to_string(), &[])]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+    deps.querier
+        .with_pools(&[(&"pair0000".to_string(), &clsm_lunc_pool(1_000, 1_000))]);
+
+    // only the timer trigger can move the minter
+    let info = mock_info("addr0000", &[]);
+    let msg = MoonExecuteMsg::UpdateMinterAddr {
+        new_minter: "minter0001".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the minter can't be the token or the timer trigger
+    let info = mock_info("timer0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        MoonExecuteMsg::UpdateMinterAddr {
+            new_minter: "clsm0000".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DuplicatedAddress {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_minter_addr"),
+            attr("old_minter_addr", "minter0000"),
+            attr("new_minter_addr", "minter0001"),
+        ]
+    );
+
+    // the dynamic mint goes through the new minter
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1_000u128),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "minter0001".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "pair0000".to_string(),
+                amount: Uint128::from(2_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+}
+
+#[cfg(feature = "debug")]
+#[test]
+fn test_query_raw_config() {
+    use crate::contract::query_raw_config;
+    use classic_terraswap::moon::RawAddress;
+    use cosmwasm_std::Binary;
+
+    let mut deps = mock_dependencies(&[]);
 
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // a corrupted address is still listed, with no humanized form
+    let mut moon_config = MOON_CONFIG.load(deps.as_ref().storage).unwrap();
+    moon_config.lunc_recipient = CanonicalAddr::from(vec![1u8, 2, 3]);
+    MOON_CONFIG
+        .save(deps.as_mut().storage, &moon_config)
+        .unwrap();
+
+    let raw_address = |field: &str, addr: &str| RawAddress {
+        field: field.to_string(),
+        canonical: Binary::from(deps.api.addr_canonicalize(addr).unwrap().as_slice()),
+        humanized: Some(Addr::unchecked(addr)),
     };
+    let expected = vec![
+        raw_address("clsm_addr", "clsm0000"),
+        RawAddress {
+            field: "lunc_recipient".to_string(),
+            canonical: Binary::from(vec![1u8, 2, 3]),
+            humanized: None,
+        },
+        raw_address("minter_addr", "minter0000"),
+        raw_address("treasury_addr", "treasury0000"),
+        raw_address("authorized", "timer0000"),
+        raw_address("pair_vest", "pair0000"),
+        raw_address("nft_vest", "nft0000"),
+        raw_address("marketing_vest", "marketing0000"),
+        raw_address("game_vest", "game0000"),
+        raw_address("team_vest", "team0000"),
+    ];
+
+    let res = query_raw_config(deps.as_ref()).unwrap();
+    assert_eq!(res.addresses, expected);
+}
+
+#[test]
+fn test_authorized_callers() {
+    let mut deps = mock_dependencies(&[]);
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    // only an authorized caller can add another one
+    let info = mock_info("addr0000", &[]);
+    let msg = MoonExecuteMsg::AddAuthorized {
+        address: "timer0001".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
     assert_eq!(
-        res.assets,
-        [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: asset_1_amount
-            }
+        res.attributes,
+        vec![
+            attr("action", "add_authorized"),
+            attr("address", "timer0001"),
         ]
     );
-    assert_eq!(res.total_share, total_share_amount);
-}
 
-#[test]
-fn test_assert_minimum_assets_with_equals() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::DuplicatedAddress {});
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+    let moon_info = query_moon_info(deps.as_ref()).unwrap();
+    assert_eq!(
+        moon_info.authorized,
+        vec![Addr::unchecked("timer0000"), Addr::unchecked("timer0001")]
+    );
 
-    assert_minimum_assets(assets, minimum_assets).unwrap();
-}
+    // both callers can run the scheduled operations
+    for sender in ["timer0000", "timer0001"] {
+        let info = mock_info(sender, &[]);
+        let msg = MoonExecuteMsg::SetPaused { paused: false };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
 
-#[test]
-fn test_assert_minimum_assets_with_normal() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ];
+    // a removed caller loses its permission
+    let info = mock_info("timer0001", &[]);
+    let msg = MoonExecuteMsg::RemoveAuthorized {
+        address: "timer0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "remove_authorized"),
+            attr("address", "timer0000"),
+        ]
+    );
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    assert_minimum_assets(assets, minimum_assets).unwrap();
+    let info = mock_info("timer0001", &[]);
+    let msg = MoonExecuteMsg::RemoveAuthorized {
+        address: "timer0002".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotAuthorized {
+            address: "timer0002".to_string()
+        }
+    );
+
+    // the last caller stays
+    let info = mock_info("timer0001", &[]);
+    let msg = MoonExecuteMsg::RemoveAuthorized {
+        address: "timer0001".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::LastAuthorized {});
 }
 
 #[test]
-fn test_assert_minimum_assets_with_less_all() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+fn test_instantiate_validation() {
+    let mut deps = mock_dependencies(&[]);
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ]);
+    let mut msg = moon_instantiate_msg();
+    msg.nft_vest.monthly_amount = Uint128::zero();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidVestingSchedule {
+            bucket: Bucket::Nft
+        }
+    );
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    let mut msg = moon_instantiate_msg();
+    msg.team_vest.month_count = Uint128::zero();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
+        ContractError::InvalidVestingSchedule {
+            bucket: Bucket::Team
         }
-    )
+    );
+
+    let mut msg = moon_instantiate_msg();
+    msg.minter_addr = "clsm0000".to_string();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::DuplicatedAddress {});
+
+    let mut msg = moon_instantiate_msg();
+    msg.timer_trigger = "minter0000".to_string();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::DuplicatedAddress {});
+
+    // addresses must be valid
+    let mut msg = moon_instantiate_msg();
+    msg.clsm_addr = "Clsm0000".to_string();
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
 }
 
 #[test]
-fn test_assert_minimum_assets_with_less_second_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+fn test_query_can_emit() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(50u128))],
+    )]);
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    let res = query_can_emit(deps.as_ref(), mock_env(), Bucket::Pair).unwrap();
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uusd".to_string(),
-            asset: "1uusd".to_string()
+        res,
+        CanEmitResponse {
+            ok: false,
+            reason: Some("Less CLSM amount than vesting amount".to_string()),
+            amount: Uint128::from(100u128),
         }
-    )
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let res = query_can_emit(deps.as_ref(), mock_env(), Bucket::Pair).unwrap();
+    assert_eq!(
+        res,
+        CanEmitResponse {
+            ok: true,
+            reason: None,
+            amount: Uint128::from(100u128),
+        }
+    );
 }
 
 #[test]
-fn test_assert_minimum_assets_with_less_first_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+fn test_query_all_emission_status() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(250u128))],
+    )]);
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    // the 250 CLSM fund the first two buckets
+    let res = query_all_emission_status(deps.as_ref(), mock_env()).unwrap();
+    let underfunded = Some("Less CLSM amount than vesting amount".to_string());
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
-        }
-    )
+        res.statuses,
+        Bucket::all()
+            .iter()
+            .map(|bucket| EmissionStatus {
+                bucket: *bucket,
+                ready: matches!(bucket, Bucket::Pair | Bucket::Nft),
+                amount: Uint128::from(100u128),
+                reason: match bucket {
+                    Bucket::Pair | Bucket::Nft => None,
+                    _ => underfunded.clone(),
+                },
+            })
+            .collect::<Vec<EmissionStatus>>()
+    );
+
+    // the balance is read once for the five buckets
+    let queries = deps.querier.query_count();
+    query_all_emission_status(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(deps.querier.query_count() - queries, 1);
 }
 
 #[test]
-fn test_assert_minimum_assets_with_unsorted_less_first_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+fn test_query_next_emission() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
+    )]);
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    // a bucket which never emitted is due
+    let res = query_next_emission(deps.as_ref(), mock_env(), Bucket::Team).unwrap();
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
+        res,
+        NextEmissionResponse {
+            ready: true,
+            seconds_remaining: 0,
         }
-    )
+    );
+
+    let info = mock_info("timer0000", &[]);
+    let msg = MoonExecuteMsg::Emit {
+        bucket: Bucket::Team,
+        to: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the countdown follows the block time until the interval has passed
+    let mut env = mock_env();
+    for (elapsed, seconds_remaining) in [
+        (0u64, 2_592_000u64),
+        (86_400u64, 2_505_600u64),
+        (2_591_999u64, 1u64),
+    ] {
+        env.block.time = mock_env().block.time.plus_seconds(elapsed);
+        let res = query_next_emission(deps.as_ref(), env.clone(), Bucket::Team).unwrap();
+        assert_eq!(
+            res,
+            NextEmissionResponse {
+                ready: false,
+                seconds_remaining,
+            }
+        );
+    }
+
+    env.block.time = mock_env().block.time.plus_seconds(2_592_000);
+    let res = query_next_emission(deps.as_ref(), env, Bucket::Team).unwrap();
+    assert_eq!(
+        res,
+        NextEmissionResponse {
+            ready: true,
+            seconds_remaining: 0,
+        }
+    );
 }
 
-#[test]
-fn test_assert_minimum_assets_with_unknown_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
+fn clsm_lunc_pool(clsm_reserve: u128, lunc_reserve: u128) -> PairPoolResponse {
+    PairPoolResponse {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "clsm0000".to_string(),
+                },
+                amount: Uint128::from(clsm_reserve),
             },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                amount: Uint128::from(lunc_reserve),
             },
-            amount: Uint128::from(2u128),
-        },
-    ];
+        ],
+        total_share: Uint128::zero(),
+    }
+}
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "ukrw".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+#[test]
+fn test_batch_transfer() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(7_000u128))],
+    )]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, moon_instantiate_msg()).unwrap();
+
+    // the five buckets reserve 5 * 1_200, which leaves a surplus of 1_000
+    let msg = MoonExecuteMsg::BatchTransfer {
+        transfers: vec![
+            ("addr0001".to_string(), Uint128::from(100u128)),
+            ("addr0002".to_string(), Uint128::from(200u128)),
+            ("addr0003".to_string(), Uint128::from(700u128)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("timer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let transfer_msg = |recipient: &str, amount: u128| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    };
+    assert_eq!(
+        res.messages,
+        vec![
+            transfer_msg("addr0001", 100),
+            transfer_msg("addr0002", 200),
+            transfer_msg("addr0003", 700),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "batch_transfer"),
+            attr("total", "1000"),
+            attr("surplus", "1000"),
+        ]
+    );
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    // one more CLSM would eat into the vesting
+    let msg = MoonExecuteMsg::BatchTransfer {
+        transfers: vec![
+            ("addr0001".to_string(), Uint128::from(100u128)),
+            ("addr0002".to_string(), Uint128::from(200u128)),
+            ("addr0003".to_string(), Uint128::from(701u128)),
+        ],
+    };
+    let info = mock_info("timer0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
     assert_eq!(
         err,
-        ContractError::MinAmountAssertion {
-            min_asset: "1ukrw".to_string(),
-            asset: "0ukrw".to_string()
+        ContractError::SurplusExceeded {
+            total: Uint128::from(1_001u128),
+            surplus: Uint128::from(1_000u128),
         }
-    )
+    );
 }
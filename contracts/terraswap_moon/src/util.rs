@@ -1,12 +1,22 @@
 use crate::error::ContractError;
+use classic_bindings::TerraQuery;
 use cosmwasm_std::{
-    to_binary, Addr, BalanceResponse as NativeBalanceResponse, BankMsg, BankQuery, Coin, CosmosMsg,
-    QuerierWrapper, QueryRequest, Uint128, WasmMsg, WasmQuery,
+    to_binary, Addr, BalanceResponse as NativeBalanceResponse, BankMsg, BankQuery, Binary, Coin,
+    CosmosMsg, QuerierWrapper, QueryRequest, Uint128, WasmMsg, WasmQuery,
 };
-use cw20::{BalanceResponse as CW20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+use cw20::{
+    AllowanceResponse, BalanceResponse as CW20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom,
+};
+
+/// Address the burned native tokens are sent to
+pub const BURN_ADDRESS: &str = "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu";
+
+pub fn is_burn_address(addr: &Addr) -> bool {
+    addr.as_str() == BURN_ADDRESS
+}
 
 pub fn get_token_amount(
-    querier: QuerierWrapper,
+    querier: &QuerierWrapper<TerraQuery>,
     denom: Denom,
     contract_addr: Addr,
 ) -> Result<Uint128, ContractError> {
@@ -48,6 +58,15 @@ pub fn transfer_token_message(
             }
             .into());
         }
+        // Cw20 tokens sent to the burn address are burned instead of being
+        // stuck there while still counted in the supply
+        Denom::Cw20(cw20_address) if is_burn_address(&receiver) => {
+            return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: cw20_address.into(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            }));
+        }
         Denom::Cw20(cw20_address) => {
             return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: cw20_address.clone().into(),
@@ -61,6 +80,29 @@ pub fn transfer_token_message(
     }
 }
 
+/// Sends Cw20 tokens with the memo as the `Send` msg for the receiver to
+/// account for, or falls back to a plain transfer without a memo. Native
+/// transfers carry no payload, so they never hold the memo
+pub fn transfer_token_message_with_memo(
+    denom: Denom,
+    amount: Uint128,
+    receiver: Addr,
+    memo: Option<Binary>,
+) -> Result<CosmosMsg, ContractError> {
+    match (denom, memo) {
+        (Denom::Cw20(cw20_address), Some(memo)) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cw20_address.into(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: receiver.into(),
+                amount,
+                msg: memo,
+            })?,
+        })),
+        (denom, _) => transfer_token_message(denom, amount, receiver),
+    }
+}
+
 pub fn transfer_from_token_message(
     owner: Addr,
     denom: Denom,
@@ -84,3 +126,30 @@ pub fn transfer_from_token_message(
         }
     }
 }
+
+/// Fails when the owner has not allowed the spender to use the needed amount
+pub fn assert_allowance(
+    querier: &QuerierWrapper<TerraQuery>,
+    token_addr: Addr,
+    owner: Addr,
+    spender: Addr,
+    needed: Uint128,
+) -> Result<(), ContractError> {
+    let allowance_response: AllowanceResponse =
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: token_addr.into(),
+            msg: to_binary(&Cw20QueryMsg::Allowance {
+                owner: owner.into(),
+                spender: spender.into(),
+            })?,
+        }))?;
+
+    if allowance_response.allowance < needed {
+        return Err(ContractError::InsufficientAllowance {
+            needed,
+            granted: allowance_response.allowance,
+        });
+    }
+
+    Ok(())
+}
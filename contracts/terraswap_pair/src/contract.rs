@@ -1,30 +1,40 @@
 use crate::error::ContractError;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{Config, CONFIG, PAIR_INFO};
+use crate::state::{
+    Config, CumulativePrices, PendingProvision, ACCRUED_FEES, CONFIG, CUMULATIVE_PRICES,
+    LEGACY_CONFIG, PAIR_INFO, PENDING_PROVISION, PENDING_TEAM_ADDR, RESERVES,
+};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
     from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Decimal256, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    Uint256, WasmMsg,
+    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgResult, Uint128, Uint256, WasmMsg,
 };
 
 use classic_bindings::{TerraMsg, TerraQuery};
 
-use classic_terraswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw};
+use classic_terraswap::asset::{
+    assert_sent_native_token_balances, Asset, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw,
+};
 use classic_terraswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    AccruedFeesResponse, ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg, ReserveAuditResponse,
+    ReverseSimulationResponse, SimulateWithdrawResponse, SimulationResponse, SimulationResponseV2,
+    SpotPriceResponse,
 };
 use classic_terraswap::moon::{
     MoonExecuteMsg
 };
 use classic_terraswap::querier::{query_token_info, query_token_balance};
 use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
-use classic_terraswap::util::{assert_deadline, migrate_version};
-use cw2::set_contract_version;
+use classic_terraswap::util::{
+    assert_deadline, assert_no_downgrade, base_attrs, from_decimal, migrate_version, to_decimal,
+    Rounding,
+};
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use protobuf::Message;
 use std::cmp::Ordering;
@@ -36,11 +46,21 @@ const CONTRACT_NAME: &str = "crates.io:terraswap-pair";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const INSTANTIATE_REPLY_ID: u64 = 1;
+const PROVISION_REPLY_ID: u64 = 2;
+const MOON_VESTING_REPLY_ID: u64 = 3;
+const MOON_BURN_REPLY_ID: u64 = 4;
+
+/// Default commission rate == 0.2%
+const DEFAULT_COMMISSION_RATE: u64 = 2;
 
-/// Commission rate == 0.2%
-const COMMISSION_RATE: u64 = 2;
+/// Max commission rate == 10%
+const MAX_COMMISSION_RATE: u64 = 10;
 
-const MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
+/// Default share of the commission sent to the team == 50%
+const DEFAULT_PROTOCOL_FEE_FRACTION: u64 = 50;
+
+/// Default LP tokens locked at the first provision
+const DEFAULT_MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
 const DAY_SECONDS: u64 = 86400;
 
 const BURN_ADDRESS: &str = "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu";
@@ -52,9 +72,38 @@ pub fn instantiate(
     env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response<TerraMsg>> {
+) -> Result<Response<TerraMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    if msg.asset_infos[0].equal(&msg.asset_infos[1]) {
+        return Err(ContractError::DuplicateAsset {});
+    }
+
+    // a token asset must answer its token info, the decimals it reports
+    // are recorded
+    let mut asset_decimals = msg.asset_decimals;
+    for (i, asset_info) in msg.asset_infos.iter().enumerate() {
+        if let AssetInfo::Token { contract_addr } = asset_info {
+            asset_decimals[i] = query_token_info(&deps.querier, Addr::unchecked(contract_addr))
+                .map_err(|_| ContractError::InvalidAsset {
+                    asset: contract_addr.to_string(),
+                })?
+                .decimals;
+        }
+    }
+
+    let commission_rate = msg
+        .commission_rate
+        .unwrap_or_else(|| Decimal::permille(DEFAULT_COMMISSION_RATE));
+    assert_commission_rate(commission_rate)?;
+
+    let protocol_fee_fraction = msg
+        .protocol_fee_fraction
+        .unwrap_or_else(|| Decimal::percent(DEFAULT_PROTOCOL_FEE_FRACTION));
+    if protocol_fee_fraction > Decimal::one() {
+        return Err(ContractError::InvalidProtocolFeeFraction {});
+    }
+
     let pair_info: &PairInfoRaw = &PairInfoRaw {
         contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
         liquidity_token: CanonicalAddr::from(vec![]),
@@ -62,7 +111,7 @@ pub fn instantiate(
             msg.asset_infos[0].to_raw(deps.api)?,
             msg.asset_infos[1].to_raw(deps.api)?,
         ],
-        asset_decimals: msg.asset_decimals,
+        asset_decimals,
     };
     PAIR_INFO.save(deps.storage, pair_info)?;
 
@@ -74,9 +123,24 @@ pub fn instantiate(
         ustc_dynamic_mint: false,
         clsm_addr: Addr::unchecked(msg.clsm_addr.as_str()),
         moon_addr: None,
+        commission_rate,
+        protocol_fee_fraction,
+        minimum_liquidity: msg
+            .minimum_liquidity
+            .unwrap_or_else(|| Uint128::from(DEFAULT_MINIMUM_LIQUIDITY_AMOUNT)),
+        measure_received: msg.measure_received.unwrap_or(false),
     };
     CONFIG.save(deps.storage, config)?;
-    
+
+    CUMULATIVE_PRICES.save(
+        deps.storage,
+        &CumulativePrices {
+            price_0_cumulative_last: Decimal256::zero(),
+            price_1_cumulative_last: Decimal256::zero(),
+            block_time_last: env.block.time.seconds(),
+        },
+    )?;
+
     Ok(Response::new().add_submessage(SubMsg {
         // Create LP token
         msg: WasmMsg::Instantiate {
@@ -116,6 +180,7 @@ pub fn execute(
             receiver,
             deadline,
             slippage_tolerance,
+            min_lp_tokens,
         } => provide_liquidity(
             deps,
             env,
@@ -124,6 +189,7 @@ pub fn execute(
             receiver,
             deadline,
             slippage_tolerance,
+            min_lp_tokens,
         ),
         ExecuteMsg::Swap {
             offer_asset,
@@ -152,9 +218,18 @@ pub fn execute(
                 max_spread,
                 to_addr,
                 deadline,
+                None,
             )
         },
         ExecuteMsg::SetMoonAddress {moon_addr} => set_moon_address(deps, env, info, moon_addr),
+        ExecuteMsg::UpdateCommission { commission_rate } => {
+            update_commission(deps, env, info, commission_rate)
+        }
+        ExecuteMsg::ProposeAdmin { new } => propose_admin(deps, info, new),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, info),
+        ExecuteMsg::CollectDust { assets } => collect_dust(deps, env, info, assets),
+        ExecuteMsg::ClaimFees {} => claim_fees(deps, info),
+        ExecuteMsg::SyncReserves {} => sync_reserves(deps, info),
     
     }
 }
@@ -173,16 +248,19 @@ pub fn receive_cw20(
             max_spread,
             to,
             deadline,
+            minimum_receive,
         }) => {
             // only asset contract can execute this message
             let mut authorized: bool = false;
+            let mut offer_index: usize = 0;
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
             let pools: [Asset; 2] =
                 config.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
-            for pool in pools.iter() {
+            for (i, pool) in pools.iter().enumerate() {
                 if let AssetInfo::Token { contract_addr, .. } = &pool.info {
                     if contract_addr == &info.sender {
                         authorized = true;
+                        offer_index = i;
                     }
                 }
             }
@@ -191,6 +269,19 @@ pub fn receive_cw20(
                 return Err(ContractError::Unauthorized {});
             }
 
+            // the balance already holds the transfer, so the received amount
+            // is whatever it holds above the ledger
+            let mut offer_amount = cw20_msg.amount;
+            if CONFIG.load(deps.storage)?.measure_received {
+                if let Some(reserves) = RESERVES.may_load(deps.storage)? {
+                    let fees = ACCRUED_FEES.may_load(deps.storage)?.unwrap_or_default();
+                    let received = pools[offer_index]
+                        .amount
+                        .checked_sub(reserves[offer_index] + fees[offer_index])?;
+                    offer_amount = offer_amount.min(received);
+                }
+            }
+
             let to_addr = if let Some(to_addr) = to {
                 Some(deps.api.addr_validate(to_addr.as_str())?)
             } else {
@@ -206,12 +297,13 @@ pub fn receive_cw20(
                     info: AssetInfo::Token {
                         contract_addr: contract_addr.to_string(),
                     },
-                    amount: cw20_msg.amount,
+                    amount: offer_amount,
                 },
                 belief_price,
                 max_spread,
                 to_addr,
                 deadline,
+                minimum_receive,
             )
         }
         Ok(Cw20HookMsg::WithdrawLiquidity {
@@ -238,13 +330,54 @@ pub fn receive_cw20(
     }
 }
 
-/// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut<TerraQuery>, _env: Env, msg: Reply) -> StdResult<Response<TerraMsg>> {
-    if msg.id != INSTANTIATE_REPLY_ID {
-        return Err(StdError::generic_err("invalid reply msg"));
+pub fn reply(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    msg: Reply,
+) -> Result<Response<TerraMsg>, ContractError> {
+    match msg.id {
+        INSTANTIATE_REPLY_ID => Ok(instantiate_reply(deps, msg)?),
+        PROVISION_REPLY_ID => provision_reply(deps, env),
+        MOON_VESTING_REPLY_ID | MOON_BURN_REPLY_ID => moon_reply(deps, env, msg),
+        _ => Err(StdError::generic_err("invalid reply msg").into()),
     }
+}
 
+/// A failed vesting or burn of the moon contract is reverted on its own,
+/// the swap which triggered it goes through. Its count is moved back to the
+/// last period, so the next swap sends the call again instead of skipping
+/// the period
+fn moon_reply(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    msg: Reply,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let error = match msg.result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => String::new(),
+    };
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let days: u64 = env.block.time.seconds() / DAY_SECONDS;
+    let call = if msg.id == MOON_VESTING_REPLY_ID {
+        config.mint_count = days.saturating_sub(1) / 30;
+        "vesting_mint"
+    } else {
+        config.burn_count = days.saturating_sub(1) / 10;
+        "automatic_burn"
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "moon_call_failed"),
+        ("call", call),
+        ("error", &error),
+    ]))
+}
+
+/// This just stores the result for future query
+fn instantiate_reply(deps: DepsMut<TerraQuery>, msg: Reply) -> StdResult<Response<TerraMsg>> {
     let data = msg.result.unwrap().data.unwrap();
     let res: MsgInstantiateContractResponse =
         Message::parse_from_bytes(data.as_slice()).map_err(|_| {
@@ -270,16 +403,15 @@ pub fn provide_liquidity(
     receiver: Option<String>,
     deadline: Option<u64>,
     slippage_tolerance: Option<Decimal>,
+    min_lp_tokens: Option<Uint128>,
 ) -> Result<Response<TerraMsg>, ContractError> {
     assert_deadline(env.block.time.seconds(), deadline)?;
 
-    for asset in assets.iter() {
-        asset.assert_sent_native_token_balance(&info)?;
-    }
+    assert_sent_native_token_balances(&assets, &info)?;
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let mut pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+        query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
     let deposits: [Uint128; 2] = [
         assets
             .iter()
@@ -293,7 +425,31 @@ pub fn provide_liquidity(
             .expect("Wrong asset info is given"),
     ];
 
-    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+
+    // a token taking a fee on transfer is only counted once it is received
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.measure_received
+        && pools
+            .iter()
+            .zip(deposits.iter())
+            .any(|(pool, deposit)| !pool.is_native_token() && !deposit.is_zero())
+    {
+        return pull_provision(
+            deps,
+            env,
+            &pools,
+            PendingProvision {
+                sender: info.sender,
+                receiver,
+                deposits,
+                balances_before: [Uint128::zero(); 2],
+                slippage_tolerance,
+                min_lp_tokens,
+            },
+        );
+    }
+
     for (i, pool) in pools.iter_mut().enumerate() {
         if pool.is_native_token() {
             // If the asset is native token, balance is already increased
@@ -302,22 +458,50 @@ pub fn provide_liquidity(
         }
     }
 
+    execute_provision(
+        deps,
+        env,
+        pair_info,
+        pools,
+        deposits,
+        info.sender,
+        receiver,
+        slippage_tolerance,
+        min_lp_tokens,
+        false,
+    )
+}
+
+/// Mints the LP tokens for deposits already checked against the reserves
+/// they are added to. The received token deposits are already in the pair,
+/// otherwise they are transferred from the sender
+#[allow(clippy::too_many_arguments)]
+fn execute_provision(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    pair_info: PairInfoRaw,
+    pools: [Asset; 2],
+    deposits: [Uint128; 2],
+    sender: Addr,
+    receiver: String,
+    slippage_tolerance: Option<Decimal>,
+    min_lp_tokens: Option<Uint128>,
+    received: bool,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    accumulate_prices(
+        deps.storage,
+        env.block.time.seconds(),
+        [pools[0].amount, pools[1].amount],
+    )?;
+
     let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
     let total_share = query_token_info(&deps.querier, liquidity_token)?.total_supply;
     let share = if total_share.is_zero() {
-        // Initial share = collateral amount
-        let deposit0: Uint256 = deposits[0].into();
-        let deposit1: Uint256 = deposits[1].into();
-        let share: Uint128 = match (Decimal256::from_ratio(deposit0.mul(deposit1), 1u8).sqrt()
-            * Uint256::from(1u8))
-        .try_into()
-        {
-            Ok(share) => share,
-            Err(e) => return Err(ContractError::ConversionOverflowError(e)),
-        };
-
-        // the initial liquidity is deducted by MINIMUM_LIQUIDITY_AMOUNT
+        // the initial liquidity is deducted by the minimum liquidity
         // to protect a pair from malicious provision blocking
+        let minimum_liquidity = CONFIG.load(deps.storage)?.minimum_liquidity;
+        let share = compute_initial_share(deposits, minimum_liquidity)?;
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -325,17 +509,12 @@ pub fn provide_liquidity(
                 .to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Mint {
                 recipient: env.contract.address.to_string(),
-                amount: MINIMUM_LIQUIDITY_AMOUNT.into(),
+                amount: minimum_liquidity,
             })?,
             funds: vec![],
         }));
 
         share
-            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT.into())
-            .map_err(|_| ContractError::MinimumLiquidityAmountError {
-                min_lp_token: MINIMUM_LIQUIDITY_AMOUNT.to_string(),
-                given_lp: share.to_string(),
-            })?
     } else {
         // min(1, 2)
         // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
@@ -353,8 +532,18 @@ pub fn provide_liquidity(
         return Err(ContractError::InvalidZeroAmount {});
     }
 
+    if let Some(min_lp_tokens) = min_lp_tokens {
+        if share < min_lp_tokens {
+            return Err(ContractError::SlippageExceeded {
+                min_lp_tokens: min_lp_tokens.to_string(),
+                share: share.to_string(),
+            });
+        }
+    }
+
     // refund of remaining native token & desired of token
     let mut refund_assets: Vec<Asset> = vec![];
+    let mut provided: [Uint128; 2] = [Uint128::zero(); 2];
     for (i, pool) in pools.iter().enumerate() {
         let desired_amount = match total_share.is_zero() {
             true => deposits[i],
@@ -369,6 +558,7 @@ pub fn provide_liquidity(
         };
 
         let remain_amount = deposits[i] - desired_amount;
+        provided[i] = desired_amount;
         if let Some(slippage_tolerance) = slippage_tolerance {
             if remain_amount > deposits[i] * slippage_tolerance {
                 return Err(ContractError::MaxSlippageAssertion {});
@@ -387,25 +577,37 @@ pub fn provide_liquidity(
                         denom: denom.to_string(),
                     },
                 }
-                .into_msg(&deps.querier, info.sender.clone())?;
+                .into_msg(&deps.querier, sender.clone())?;
 
                 messages.push(msg);
             }
         } else if let AssetInfo::Token { contract_addr, .. } = &pool.info {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: contract_addr.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: env.contract.address.to_string(),
-                    amount: desired_amount,
-                })?,
-                funds: vec![],
-            }));
+            if !received {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: desired_amount,
+                    })?,
+                    funds: vec![],
+                }));
+            } else if !remain_amount.is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: sender.to_string(),
+                        amount: remain_amount,
+                    })?,
+                    funds: vec![],
+                }));
+            }
         }
     }
 
+    update_reserves(deps.storage, provided, [Uint128::zero(); 2])?;
+
     // mint LP token to sender
-    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: deps
             .api
@@ -420,9 +622,15 @@ pub fn provide_liquidity(
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         ("action", "provide_liquidity"),
-        ("sender", info.sender.as_str()),
+        ("sender", sender.as_str()),
         ("receiver", receiver.as_str()),
-        ("assets", &format!("{}, {}", assets[0], assets[1])),
+        (
+            "assets",
+            &format!(
+                "{}{}, {}{}",
+                deposits[0], pools[0].info, deposits[1], pools[1].info
+            ),
+        ),
         ("share", &share.to_string()),
         (
             "refund_assets",
@@ -431,6 +639,95 @@ pub fn provide_liquidity(
     ]))
 }
 
+/// Transfers the token deposits in and leaves the provision pending until
+/// the reply of the last transfer measures what the pair received
+fn pull_provision(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    pools: &[Asset; 2],
+    mut pending: PendingProvision,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut messages: Vec<SubMsg<TerraMsg>> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        if let AssetInfo::Token { contract_addr } = &pool.info {
+            if pending.deposits[i].is_zero() {
+                continue;
+            }
+
+            pending.balances_before[i] = query_token_balance(
+                &deps.querier,
+                Addr::unchecked(contract_addr),
+                env.contract.address.clone(),
+            )?;
+            messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: pending.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: pending.deposits[i],
+                })?,
+                funds: vec![],
+            })));
+        }
+    }
+
+    // the messages run in order, so every transfer is done by the last reply
+    if let Some(last) = messages.last_mut() {
+        last.id = PROVISION_REPLY_ID;
+        last.reply_on = ReplyOn::Success;
+    }
+    PENDING_PROVISION.save(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            ("action", "pull_provision"),
+            ("sender", pending.sender.as_str()),
+        ]))
+}
+
+/// Completes the pending provision with the token amounts the pair received
+fn provision_reply(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let pending = PENDING_PROVISION.load(deps.storage)?;
+    PENDING_PROVISION.remove(deps.storage);
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let mut pools: [Asset; 2] =
+        query_reserves(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let mut deposits = pending.deposits;
+    for (i, pool) in pools.iter_mut().enumerate() {
+        if let AssetInfo::Token { contract_addr } = &pool.info {
+            if !deposits[i].is_zero() {
+                let balance = query_token_balance(
+                    &deps.querier,
+                    Addr::unchecked(contract_addr),
+                    env.contract.address.clone(),
+                )?;
+                deposits[i] = balance.checked_sub(pending.balances_before[i])?;
+            }
+        }
+
+        // the deposits are already in the balances
+        pool.amount = pool.amount.checked_sub(deposits[i])?;
+    }
+
+    execute_provision(
+        deps,
+        env,
+        pair_info,
+        pools,
+        deposits,
+        pending.sender,
+        pending.receiver,
+        pending.slippage_tolerance,
+        pending.min_lp_tokens,
+        true,
+    )
+}
+
 pub fn withdraw_liquidity(
     deps: DepsMut<TerraQuery>,
     env: Env,
@@ -445,20 +742,25 @@ pub fn withdraw_liquidity(
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+    let pools: [Asset; 2] = query_reserves(deps.as_ref(), &pair_info, env.contract.address)?;
     let total_share: Uint128 = query_token_info(&deps.querier, liquidity_addr)?.total_supply;
+    let minimum_liquidity = CONFIG.load(deps.storage)?.minimum_liquidity;
+    let refund_assets = compute_withdraw(&pools, amount, total_share, minimum_liquidity)?;
 
-    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
-    let refund_assets: Vec<Asset> = pools
-        .iter()
-        .map(|a| Asset {
-            info: a.info.clone(),
-            amount: a.amount * share_ratio,
-        })
-        .collect();
+    accumulate_prices(
+        deps.storage,
+        env.block.time.seconds(),
+        [pools[0].amount, pools[1].amount],
+    )?;
 
     assert_minimum_assets(refund_assets.to_vec(), min_assets)?;
 
+    update_reserves(
+        deps.storage,
+        [Uint128::zero(); 2],
+        [refund_assets[0].amount, refund_assets[1].amount],
+    )?;
+
     // update pool info
     Ok(Response::new()
         .add_messages(vec![
@@ -501,6 +803,7 @@ pub fn swap(
     max_spread: Option<Decimal>,
     to: Option<Addr>,
     deadline: Option<u64>,
+    minimum_receive: Option<Uint128>,
 ) -> Result<Response<TerraMsg>, ContractError> {
     let _env = env.clone();
     assert_deadline(env.block.time.seconds(), deadline)?;
@@ -509,16 +812,19 @@ pub fn swap(
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+    let pools: [Asset; 2] = query_reserves(deps.as_ref(), &pair_info, env.contract.address)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
 
     let offer_decimal: u8;
     let ask_decimal: u8;
+    let ask_index: usize;
+    let reserves: [Uint128; 2];
+    let offer_info = offer_asset.info.to_normalized(deps.api)?;
     // If the asset balance is already increased
     // To calculated properly we should subtract user deposit from the pool
-    if offer_asset.info.equal(&pools[0].info) {
+    if offer_info.equal(&pools[0].info) {
         offer_pool = Asset {
             amount: pools[0].amount.checked_sub(offer_asset.amount)?,
             info: pools[0].info.clone(),
@@ -527,7 +833,9 @@ pub fn swap(
 
         offer_decimal = pair_info.asset_decimals[0];
         ask_decimal = pair_info.asset_decimals[1];
-    } else if offer_asset.info.equal(&pools[1].info) {
+        ask_index = 1;
+        reserves = [offer_pool.amount, ask_pool.amount];
+    } else if offer_info.equal(&pools[1].info) {
         offer_pool = Asset {
             amount: pools[1].amount.checked_sub(offer_asset.amount)?,
             info: pools[1].info.clone(),
@@ -536,13 +844,36 @@ pub fn swap(
 
         offer_decimal = pair_info.asset_decimals[1];
         ask_decimal = pair_info.asset_decimals[0];
+        ask_index = 0;
+        reserves = [ask_pool.amount, offer_pool.amount];
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
+    // an empty reserve would divide by zero in compute_swap
+    if offer_pool.amount.is_zero() || ask_pool.amount.is_zero() {
+        return Err(ContractError::NoLiquidity {});
+    }
+
+    accumulate_prices(deps.storage, env.block.time.seconds(), reserves)?;
+
+    let config = CONFIG.load(deps.storage)?;
     let offer_amount = offer_asset.amount;
-    let (return_amount, spread_amount, commission_amount) =
-        compute_swap(offer_pool.amount, ask_pool.amount, offer_amount)?;
+    let SwapResult {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    } = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        config.commission_rate,
+    )?;
+
+    // a dust offer would only pay the gas for nothing
+    if return_amount.is_zero() {
+        return Err(ContractError::SwapOutputTooSmall {});
+    }
 
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -560,60 +891,203 @@ pub fn swap(
         ask_decimal,
     )?;
 
+    if let Some(minimum_receive) = minimum_receive {
+        if return_amount < minimum_receive {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    }
+
     // compute tax
     let tax_amount = return_asset.compute_tax(&deps.querier)?;
     let receiver = to.unwrap_or_else(|| sender.clone());
 
-    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
-    if !return_amount.is_zero() {
-        messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
-    }
-    if !commission_amount.is_zero() {
-        let commission_origion_amount: Uint128 = commission_amount.into();
-        let team_amount: Uint128 = commission_origion_amount / Uint128::from(2u16);
-        let treasury_asset = Asset {
-            info: ask_pool.info.clone(),
-            amount: team_amount
-        };
-        let config = CONFIG.load(deps.storage)?;
-        messages.push(treasury_asset.into_msg(&deps.querier, config.team_addr.clone())?);
+    let mut messages: Vec<CosmosMsg<TerraMsg>> =
+        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+    // the team share of the commission accrues until the team claims it,
+    // the rest stays with the LPs
+    let team_amount: Uint128 = commission_amount * config.protocol_fee_fraction;
+    if !team_amount.is_zero() {
+        let mut accrued_fees = ACCRUED_FEES.may_load(deps.storage)?.unwrap_or_default();
+        accrued_fees[ask_index] = accrued_fees[ask_index].checked_add(team_amount)?;
+        ACCRUED_FEES.save(deps.storage, &accrued_fees)?;
     }
 
-    let timing_messages: Vec<CosmosMsg<TerraMsg>> = calc_date(deps, &_env)?;
-    messages.extend(timing_messages);
+    let mut added: [Uint128; 2] = [Uint128::zero(); 2];
+    let mut removed: [Uint128; 2] = [Uint128::zero(); 2];
+    added[1 - ask_index] = offer_amount;
+    removed[ask_index] = return_amount.checked_add(team_amount)?;
+    update_reserves(deps.storage, added, removed)?;
+
+    let timing_messages: Vec<SubMsg<TerraMsg>> = calc_date(deps, &_env)?;
 
     // 1. send collateral token from the contract to a user
-    // 2. send inactive commission to collector
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "swap"),
-        ("sender", sender.as_str()),
-        ("receiver", receiver.as_str()),
-        ("offer_asset", &offer_asset.info.to_string()),
-        ("ask_asset", &ask_pool.info.to_string()),
-        ("offer_amount", &offer_amount.to_string()),
-        ("return_amount", &return_amount.to_string()),
-        ("tax_amount", &tax_amount.to_string()),
-        ("spread_amount", &spread_amount.to_string()),
-        ("commission_amount", &commission_amount.to_string()),
-    ]))
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(timing_messages)
+        .add_attributes(vec![
+            ("action", "swap"),
+            ("sender", sender.as_str()),
+            ("receiver", receiver.as_str()),
+            ("offer_asset", &offer_asset.info.to_string()),
+            ("ask_asset", &ask_pool.info.to_string()),
+            ("offer_amount", &offer_amount.to_string()),
+            ("return_amount", &return_amount.to_string()),
+            ("tax_amount", &tax_amount.to_string()),
+            ("spread_amount", &spread_amount.to_string()),
+            ("commission_amount", &commission_amount.to_string()),
+        ])
+        .add_attributes(base_attrs()))
+}
+
+/// Pool balances without the protocol fees accrued for the team, the
+/// reserves the LPs own
+fn query_reserves(
+    deps: Deps<TerraQuery>,
+    pair_info: &PairInfoRaw,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    let mut pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let accrued_fees = ACCRUED_FEES.may_load(deps.storage)?.unwrap_or_default();
+    for (pool, accrued_fee) in pools.iter_mut().zip(accrued_fees) {
+        pool.amount = pool.amount.checked_sub(accrued_fee)?;
+    }
+
+    Ok(pools)
+}
+
+/// Moves the stored reserves by the amounts added to and taken from each
+/// pool. The removed amounts saturate, so a ledger behind the balances
+/// never underflows
+fn update_reserves(
+    storage: &mut dyn Storage,
+    added: [Uint128; 2],
+    removed: [Uint128; 2],
+) -> StdResult<()> {
+    let mut reserves = RESERVES.may_load(storage)?.unwrap_or_default();
+    for (i, reserve) in reserves.iter_mut().enumerate() {
+        *reserve = reserve.checked_add(added[i])?.saturating_sub(removed[i]);
+    }
+
+    RESERVES.save(storage, &reserves)
+}
+
+/// LP amount minted to the first provider, the geometric mean of the
+/// deposits less the minimum liquidity locked in the pair for good
+pub fn compute_initial_share(
+    deposits: [Uint128; 2],
+    minimum_liquidity: Uint128,
+) -> Result<Uint128, ContractError> {
+    let deposit0: Uint256 = deposits[0].into();
+    let deposit1: Uint256 = deposits[1].into();
+    let share: Uint128 = match (Decimal256::from_ratio(deposit0.mul(deposit1), 1u8).sqrt()
+        * Uint256::from(1u8))
+    .try_into()
+    {
+        Ok(share) => share,
+        Err(e) => return Err(ContractError::ConversionOverflowError(e)),
+    };
+
+    share
+        .checked_sub(minimum_liquidity)
+        .map_err(|_| ContractError::MinimumLiquidityAmountError {
+            min_lp_token: minimum_liquidity.to_string(),
+            given_lp: share.to_string(),
+        })
+}
+
+/// Share of each reserve refunded for the withdrawn LP amount
+fn compute_withdraw(
+    pools: &[Asset; 2],
+    amount: Uint128,
+    total_share: Uint128,
+    minimum_liquidity: Uint128,
+) -> Result<[Asset; 2], ContractError> {
+    if amount > total_share {
+        return Err(ContractError::WithdrawExceedsShare {
+            lp_amount: amount.to_string(),
+            total_share: total_share.to_string(),
+        });
+    }
+
+    // the liquidity minted to the pair at the first provision is never withdrawn
+    let remaining_share = total_share - amount;
+    if remaining_share < minimum_liquidity {
+        return Err(ContractError::MinimumLiquidityLocked {
+            min_lp_token: minimum_liquidity.to_string(),
+            remaining_lp: remaining_share.to_string(),
+        });
+    }
+
+    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
+    Ok([
+        Asset {
+            info: pools[0].info.clone(),
+            amount: pools[0].amount * share_ratio,
+        },
+        Asset {
+            info: pools[1].info.clone(),
+            amount: pools[1].amount * share_ratio,
+        },
+    ])
 }
 
+/// Adds the prices of the reserves before the state change, weighted by
+/// the time elapsed since the last update
+fn accumulate_prices(
+    storage: &mut dyn Storage,
+    block_time: u64,
+    reserves: [Uint128; 2],
+) -> Result<(), ContractError> {
+    // pairs instantiated before the accumulator start from this block
+    let mut prices = CUMULATIVE_PRICES
+        .may_load(storage)?
+        .unwrap_or(CumulativePrices {
+            price_0_cumulative_last: Decimal256::zero(),
+            price_1_cumulative_last: Decimal256::zero(),
+            block_time_last: block_time,
+        });
+
+    let elapsed = block_time.saturating_sub(prices.block_time_last);
+    if elapsed > 0 && !reserves[0].is_zero() && !reserves[1].is_zero() {
+        let elapsed = Decimal256::from_ratio(elapsed, 1u8);
+        prices.price_0_cumulative_last = prices.price_0_cumulative_last.checked_add(
+            Decimal256::from_ratio(reserves[1], reserves[0]).checked_mul(elapsed)?,
+        )?;
+        prices.price_1_cumulative_last = prices.price_1_cumulative_last.checked_add(
+            Decimal256::from_ratio(reserves[0], reserves[1]).checked_mul(elapsed)?,
+        )?;
+    }
+    prices.block_time_last = block_time;
+
+    CUMULATIVE_PRICES.save(storage, &prices)?;
+
+    Ok(())
+}
+
+/// Messages to the moon contract which are due. They are sent as
+/// submessages whose failure does not fail the swap
 pub fn calc_date(
     deps: DepsMut<TerraQuery>,
     env: &Env,
-) -> Result<Vec<CosmosMsg<TerraMsg>>, ContractError> {
-    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+) -> Result<Vec<SubMsg<TerraMsg>>, ContractError> {
+    let mut messages: Vec<SubMsg<TerraMsg>> = vec![];
 
     let mut config = CONFIG.load(deps.storage)?;
     let now_seconds: u64 = env.block.time.seconds();
     let days: u64 = now_seconds / DAY_SECONDS;
+    // the counts catch up with the current period, so a message is sent
+    // once per period rather than on every swap until they do
     if days > config.mint_count * 30 {
-        messages.push(vesting_mint(&deps)?);
-        config.mint_count += 1;
+        if let Some(message) = vesting_mint(&deps)? {
+            messages.push(SubMsg::reply_on_error(message, MOON_VESTING_REPLY_ID));
+        }
+        config.mint_count = days / 30 + 1;
     }
     if days > config.burn_count * 10 {
-        messages.push(automatic_burn(&deps)?);
-        config.burn_count += 1;
+        if let Some(message) = automatic_burn(&deps)? {
+            messages.push(SubMsg::reply_on_error(message, MOON_BURN_REPLY_ID));
+        }
+        config.burn_count = days / 10 + 1;
     }
     if days > 60 && config.lunc_dynamic_mint == false {
         config.lunc_dynamic_mint = true;
@@ -627,42 +1101,35 @@ pub fn calc_date(
     Ok(messages)
 }
 
+/// Nothing is sent until the moon contract is set
 pub fn automatic_burn(
     deps: &DepsMut<TerraQuery>,
-) -> Result<CosmosMsg<TerraMsg>, ContractError> {
+) -> Result<Option<CosmosMsg<TerraMsg>>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    let total_supply = query_token_total_supply(
-        deps.as_ref(),
-    )?;
-    let mut burn_amount = total_supply;
-    if total_supply >= Uint128::from(1000000000u64) {
-        burn_amount = total_supply / Uint128::from(4u32);
-    } else {
-        burn_amount = total_supply / Uint128::from(100u32);
+    match config.moon_addr {
+        Some(moon_address) => Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: moon_address.to_string(),
+            msg: to_binary(&MoonExecuteMsg::AutomaticBurn {})?,
+            funds: vec![],
+        }))),
+        None => Ok(None),
     }
-
-    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: config.clsm_addr.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Burn {
-            amount: burn_amount,
-        })?,
-        funds: vec![],
-    }))
 }
 
+/// Nothing is sent until the moon contract is set
 pub fn vesting_mint(
     deps: &DepsMut<TerraQuery>,
-) -> Result<CosmosMsg<TerraMsg>, ContractError> {
+) -> Result<Option<CosmosMsg<TerraMsg>>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     match config.moon_addr {
-        Some(moon_address) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        Some(moon_address) => Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: moon_address.to_string(),
-            msg: to_binary(&MoonExecuteMsg::VestingMint {})?,
+            msg: to_binary(&MoonExecuteMsg::VestingMint { to: None })?,
             funds: vec![],
-        })),
-        None => Err(ContractError::NoMoonContractAddress {})
+        }))),
+        None => Ok(None),
     }
 }
 
@@ -680,6 +1147,199 @@ pub fn set_moon_address(
     Ok(Response::new())
 }
 
+pub fn update_commission(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    commission_rate: Decimal,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.team_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    assert_commission_rate(commission_rate)?;
+
+    let old_commission_rate = config.commission_rate;
+    config.commission_rate = commission_rate;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_commission"),
+        ("old_commission_rate", &old_commission_rate.to_string()),
+        ("new_commission_rate", &commission_rate.to_string()),
+    ]))
+}
+
+pub fn propose_admin(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    new: String,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.team_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // a later proposal replaces the pending one
+    let pending_team_addr = deps.api.addr_validate(&new)?;
+    PENDING_TEAM_ADDR.save(deps.storage, &pending_team_addr)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_admin"),
+        ("pending_team_addr", pending_team_addr.as_str()),
+    ]))
+}
+
+pub fn accept_admin(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // only the proposed address can take over
+    match PENDING_TEAM_ADDR.may_load(deps.storage)? {
+        Some(pending_team_addr) if pending_team_addr == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    let old_team_addr = config.team_addr;
+    config.team_addr = info.sender.clone();
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_TEAM_ADDR.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_admin"),
+        ("old_team_addr", old_team_addr.as_str()),
+        ("new_team_addr", info.sender.as_str()),
+    ]))
+}
+
+pub fn collect_dust(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<AssetInfo>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.team_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the reserves back the pool price and the locked LP backs the minimum
+    // liquidity, so neither is dust
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let mut reserved: Vec<AssetInfoRaw> = pair_info.asset_infos.to_vec();
+    reserved.push(AssetInfoRaw::Token {
+        contract_addr: deps.api.addr_canonicalize(config.clsm_addr.as_str())?,
+    });
+    if !pair_info.liquidity_token.as_slice().is_empty() {
+        reserved.push(AssetInfoRaw::Token {
+            contract_addr: pair_info.liquidity_token,
+        });
+    }
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut collected: Vec<String> = vec![];
+    for asset_info in assets {
+        let asset_info_raw = asset_info.to_raw(deps.api)?;
+        if reserved.iter().any(|asset| asset.equal(&asset_info_raw)) {
+            return Err(ContractError::ReservedAsset {
+                asset: asset_info.to_string(),
+            });
+        }
+
+        let amount =
+            asset_info.query_pool(&deps.querier, deps.api, env.contract.address.clone())?;
+        if amount.is_zero() {
+            continue;
+        }
+
+        let asset = Asset {
+            info: asset_info,
+            amount,
+        };
+        collected.push(asset.to_string());
+        messages.push(asset.into_msg(&deps.querier, config.team_addr.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "collect_dust"),
+        ("collected", &collected.join(", ")),
+    ]))
+}
+
+pub fn claim_fees(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.team_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let accrued_fees = query_accrued_fees(deps.as_ref())?.accrued_fees;
+    if accrued_fees.iter().all(|fee| fee.amount.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    ACCRUED_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut claimed: Vec<String> = vec![];
+    for fee in accrued_fees {
+        if fee.amount.is_zero() {
+            continue;
+        }
+
+        claimed.push(fee.to_string());
+        messages.push(fee.into_msg(&deps.querier, config.team_addr.clone())?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "claim_fees"),
+        ("claimed", &claimed.join(", ")),
+    ]))
+}
+
+/// Reconciles the reserve ledger with the balances. The pool price and the
+/// LP shares come from the balances, which already include a donation, so
+/// this only keeps the surplus out of the audit and out of the amount a
+/// `measure_received` swap counts as received
+pub fn sync_reserves(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.team_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the surplus of the audit is folded into the ledger, a shortfall never
+    // lowers it
+    let audit = query_reserve_audit(deps.as_ref())?;
+    update_reserves(deps.storage, audit.surplus, [Uint128::zero(); 2])?;
+    let reserves = RESERVES.load(deps.storage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "sync_reserves"),
+        (
+            "synced",
+            &format!("{}, {}", audit.surplus[0], audit.surplus[1]),
+        ),
+        ("reserves", &format!("{}, {}", reserves[0], reserves[1])),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -688,6 +1348,9 @@ pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary,
         QueryMsg::Simulation { offer_asset } => {
             Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
         },
+        QueryMsg::SimulationV2 { offer_asset } => {
+            Ok(to_binary(&query_simulation_v2(deps, offer_asset)?)?)
+        },
         QueryMsg::ReverseSimulation { ask_asset } => {
             Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
         },
@@ -696,6 +1359,14 @@ pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary,
         },
         QueryMsg::LuncDynamicMinting {} => Ok(to_binary(&query_lunc_dynamic_minting(deps)?)?),
         QueryMsg::UstcDynamicMinting {} => Ok(to_binary(&query_ustc_dynamic_minting(deps)?)?),
+        QueryMsg::SpotPrice {} => Ok(to_binary(&query_spot_price(deps)?)?),
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps)?)?),
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            Ok(to_binary(&query_simulate_withdraw(deps, lp_amount)?)?)
+        },
+        QueryMsg::AccruedFees {} => Ok(to_binary(&query_accrued_fees(deps)?)?),
+        QueryMsg::ReserveAudit {} => Ok(to_binary(&query_reserve_audit(deps)?)?),
     }
 }
 
@@ -734,6 +1405,24 @@ pub fn query_pair_info(deps: Deps<TerraQuery>) -> Result<PairInfo, ContractError
     Ok(pair_info)
 }
 
+pub fn query_config(deps: Deps<TerraQuery>) -> Result<ConfigResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        team_addr: config.team_addr.to_string(),
+        mint_count: config.mint_count,
+        burn_count: config.burn_count,
+        lunc_dynamic_mint: config.lunc_dynamic_mint,
+        ustc_dynamic_mint: config.ustc_dynamic_mint,
+        clsm_addr: config.clsm_addr.to_string(),
+        moon_addr: config.moon_addr.map(|moon_addr| moon_addr.to_string()),
+        commission_rate: config.commission_rate,
+        protocol_fee_fraction: config.protocol_fee_fraction,
+        minimum_liquidity: config.minimum_liquidity,
+        measure_received: config.measure_received,
+    })
+}
+
 pub fn query_lunc_dynamic_minting(deps: Deps<TerraQuery>) -> Result<bool, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     let lunc_dynamic_mint = config.lunc_dynamic_mint;
@@ -751,12 +1440,17 @@ pub fn query_ustc_dynamic_minting(deps: Deps<TerraQuery>) -> Result<bool, Contra
 pub fn query_pool(deps: Deps<TerraQuery>) -> Result<PoolResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let assets: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
-    let total_share: Uint128 = query_token_info(
-        &deps.querier,
-        deps.api.addr_humanize(&pair_info.liquidity_token)?,
-    )?
-    .total_supply;
+    let assets: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
+    // the LP token only exists once the instantiate reply stored it
+    let total_share: Uint128 = if pair_info.liquidity_token.as_slice().is_empty() {
+        Uint128::zero()
+    } else {
+        query_token_info(
+            &deps.querier,
+            deps.api.addr_humanize(&pair_info.liquidity_token)?,
+        )?
+        .total_supply
+    };
 
     let resp = PoolResponse {
         assets,
@@ -766,6 +1460,107 @@ pub fn query_pool(deps: Deps<TerraQuery>) -> Result<PoolResponse, ContractError>
     Ok(resp)
 }
 
+pub fn query_simulate_withdraw(
+    deps: Deps<TerraQuery>,
+    lp_amount: Uint128,
+) -> Result<SimulateWithdrawResponse, ContractError> {
+    let pool = query_pool(deps)?;
+    let minimum_liquidity = CONFIG.load(deps.storage)?.minimum_liquidity;
+    let [asset_0, asset_1] =
+        compute_withdraw(&pool.assets, lp_amount, pool.total_share, minimum_liquidity)?;
+
+    Ok(SimulateWithdrawResponse { asset_0, asset_1 })
+}
+
+pub fn query_accrued_fees(deps: Deps<TerraQuery>) -> Result<AccruedFeesResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let accrued_fees = ACCRUED_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(AccruedFeesResponse {
+        accrued_fees: [
+            Asset {
+                info: pair_info.asset_infos[0].to_normal(deps.api)?,
+                amount: accrued_fees[0],
+            },
+            Asset {
+                info: pair_info.asset_infos[1].to_normal(deps.api)?,
+                amount: accrued_fees[1],
+            },
+        ],
+    })
+}
+
+pub fn query_reserve_audit(deps: Deps<TerraQuery>) -> Result<ReserveAuditResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let balances: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let reserves = RESERVES.may_load(deps.storage)?.unwrap_or_default();
+    let accrued_fees = ACCRUED_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+    // the balances hold the LP reserves and the fees accrued for the team
+    let mut surplus: [Uint128; 2] = [Uint128::zero(); 2];
+    let mut shortfall: [Uint128; 2] = [Uint128::zero(); 2];
+    for (i, balance) in balances.iter().enumerate() {
+        let expected = reserves[i].checked_add(accrued_fees[i])?;
+        surplus[i] = balance.amount.saturating_sub(expected);
+        shortfall[i] = expected.saturating_sub(balance.amount);
+    }
+
+    Ok(ReserveAuditResponse {
+        reserves: [
+            Asset {
+                info: balances[0].info.clone(),
+                amount: reserves[0],
+            },
+            Asset {
+                info: balances[1].info.clone(),
+                amount: reserves[1],
+            },
+        ],
+        balances,
+        surplus,
+        shortfall,
+    })
+}
+
+pub fn query_spot_price(deps: Deps<TerraQuery>) -> Result<SpotPriceResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
+
+    // an empty pool has no price
+    if pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return Ok(SpotPriceResponse {
+            asset_0_per_1: Decimal::zero(),
+            asset_1_per_0: Decimal::zero(),
+        });
+    }
+
+    Ok(SpotPriceResponse {
+        asset_0_per_1: Decimal::from_ratio(pools[0].amount, pools[1].amount),
+        asset_1_per_0: Decimal::from_ratio(pools[1].amount, pools[0].amount),
+    })
+}
+
+pub fn query_cumulative_prices(
+    deps: Deps<TerraQuery>,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let prices = CUMULATIVE_PRICES.may_load(deps.storage)?;
+
+    Ok(match prices {
+        Some(prices) => CumulativePricesResponse {
+            price_0_cumulative_last: prices.price_0_cumulative_last,
+            price_1_cumulative_last: prices.price_1_cumulative_last,
+            block_time_last: prices.block_time_last,
+        },
+        None => CumulativePricesResponse {
+            price_0_cumulative_last: Decimal256::zero(),
+            price_1_cumulative_last: Decimal256::zero(),
+            block_time_last: 0,
+        },
+    })
+}
+
 pub fn query_simulation(
     deps: Deps<TerraQuery>,
     offer_asset: Asset,
@@ -773,27 +1568,55 @@ pub fn query_simulation(
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
 
+    let offer_info = offer_asset.info.to_normalized(deps.api)?;
     let offer_pool: Asset;
     let ask_pool: Asset;
-    if offer_asset.info.equal(&pools[0].info) {
+    if offer_info.equal(&pools[0].info) {
         offer_pool = pools[0].clone();
         ask_pool = pools[1].clone();
-    } else if offer_asset.info.equal(&pools[1].info) {
+    } else if offer_info.equal(&pools[1].info) {
         offer_pool = pools[1].clone();
         ask_pool = pools[0].clone();
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
-    let (return_amount, spread_amount, commission_amount) =
-        compute_swap(offer_pool.amount, ask_pool.amount, offer_asset.amount)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let swap_result = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        config.commission_rate,
+    )?;
 
     Ok(SimulationResponse {
-        return_amount,
-        spread_amount,
-        commission_amount,
+        return_amount: swap_result.return_amount,
+        spread_amount: swap_result.spread_amount,
+        commission_amount: swap_result.commission_amount,
+    })
+}
+
+pub fn query_simulation_v2(
+    deps: Deps<TerraQuery>,
+    offer_asset: Asset,
+) -> Result<SimulationResponseV2, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+
+    let offer_index = if offer_asset.info.to_raw(deps.api)? == pair_info.asset_infos[0] {
+        0
+    } else {
+        1
+    };
+    let simulation = query_simulation(deps, offer_asset)?;
+
+    Ok(SimulationResponseV2 {
+        return_amount: simulation.return_amount,
+        spread_amount: simulation.spread_amount,
+        commission_amount: simulation.commission_amount,
+        offer_decimals: pair_info.asset_decimals[offer_index],
+        ask_decimals: pair_info.asset_decimals[1 - offer_index],
     })
 }
 
@@ -804,22 +1627,28 @@ pub fn query_reverse_simulation(
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = query_reserves(deps, &pair_info, contract_addr)?;
 
+    let ask_info = ask_asset.info.to_normalized(deps.api)?;
     let offer_pool: Asset;
     let ask_pool: Asset;
-    if ask_asset.info.equal(&pools[0].info) {
+    if ask_info.equal(&pools[0].info) {
         ask_pool = pools[0].clone();
         offer_pool = pools[1].clone();
-    } else if ask_asset.info.equal(&pools[1].info) {
+    } else if ask_info.equal(&pools[1].info) {
         ask_pool = pools[1].clone();
         offer_pool = pools[0].clone();
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
-    let (offer_amount, spread_amount, commission_amount) =
-        compute_offer_amount(offer_pool.amount, ask_pool.amount, ask_asset.amount)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        config.commission_rate,
+    )?;
 
     Ok(ReverseSimulationResponse {
         offer_amount,
@@ -828,37 +1657,53 @@ pub fn query_reverse_simulation(
     })
 }
 
+/// Breakdown of a swap, the gross output at the pool price is the sum of
+/// the three amounts
+#[derive(Debug, PartialEq, Eq)]
+struct SwapResult {
+    /// Amount received, net of the commission
+    return_amount: Uint128,
+    /// Shortfall against the pool price caused by the trade size
+    spread_amount: Uint128,
+    /// Commission kept by the pool
+    commission_amount: Uint128,
+}
+
 fn compute_swap(
     offer_pool: Uint128,
     ask_pool: Uint128,
     offer_amount: Uint128,
-) -> StdResult<(Uint128, Uint128, Uint128)> {
+    commission_rate: Decimal,
+) -> StdResult<SwapResult> {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let offer_amount: Uint256 = offer_amount.into();
 
-    let commission_rate = Decimal256::permille(COMMISSION_RATE);
+    let commission_rate: Decimal256 = commission_rate.into();
 
     // offer => ask
     // ask_amount = (ask_pool - cp / (offer_pool + offer_amount)) * (1 - commission_rate)
+    // the integer division rounds the user output down
     let return_amount: Uint256 = (ask_pool * offer_amount) / (offer_pool + offer_amount);
 
-    // calculate spread & commission
+    // calculate spread & commission, the commission rounds up in favor of the pool
     let spread_amount: Uint256 =
         (offer_amount * Decimal256::from_ratio(ask_pool, offer_pool)) - return_amount;
-    let mut commission_amount: Uint256 = return_amount * commission_rate;
-    if return_amount != (commission_amount * (Decimal256::one() / commission_rate)) {
-        commission_amount += Uint256::from(1u128);
-    }
+    let commission_amount: Uint256 = from_decimal(
+        to_decimal(return_amount.try_into()?, 0)? * commission_rate,
+        0,
+        Rounding::Ceil,
+    )?
+    .into();
 
     // commission will be absorbed to pool
     let return_amount: Uint256 = return_amount - commission_amount;
 
-    Ok((
-        return_amount.try_into()?,
-        spread_amount.try_into()?,
-        commission_amount.try_into()?,
-    ))
+    Ok(SwapResult {
+        return_amount: return_amount.try_into()?,
+        spread_amount: spread_amount.try_into()?,
+        commission_amount: commission_amount.try_into()?,
+    })
 }
 
 #[test]
@@ -867,23 +1712,122 @@ fn test_compute_swap_with_huge_pool_variance() {
     let ask_pool = Uint128::from(317u128);
 
     assert_eq!(
-        compute_swap(offer_pool, ask_pool, Uint128::from(1u128))
-            .unwrap()
-            .0,
+        compute_swap(
+            offer_pool,
+            ask_pool,
+            Uint128::from(1u128),
+            Decimal::permille(DEFAULT_COMMISSION_RATE)
+        )
+        .unwrap()
+        .return_amount,
         Uint128::zero()
     );
 }
 
+#[test]
+fn test_compute_swap_near_u128_max() {
+    // the product of the reserves and the offer overflows a Uint128, the
+    // Uint256 intermediates still give the exact result
+    let pool = Uint128::new(u128::MAX / 2);
+    assert_eq!(
+        compute_swap(
+            pool,
+            pool,
+            Uint128::from(1_000_000u128),
+            Decimal::permille(DEFAULT_COMMISSION_RATE)
+        )
+        .unwrap(),
+        SwapResult {
+            return_amount: Uint128::from(997_999u128),
+            spread_amount: Uint128::from(1u128),
+            commission_amount: Uint128::from(2_000u128),
+        }
+    );
+
+    // a spread beyond Uint128 fails the final conversion
+    let err = compute_swap(
+        Uint128::from(1u128),
+        Uint128::MAX,
+        Uint128::MAX,
+        Decimal::permille(DEFAULT_COMMISSION_RATE),
+    )
+    .unwrap_err();
+    assert!(matches!(err, StdError::ConversionOverflow { .. }));
+}
+
+#[test]
+fn test_compute_swap_breakdown() {
+    // at a pool price of 1.5 the 1_500_000_000 offered are worth 2_250_000_000
+    let swap_result = compute_swap(
+        Uint128::from(20_000_000_000u128),
+        Uint128::from(30_000_000_000u128),
+        Uint128::from(1_500_000_000u128),
+        Decimal::permille(DEFAULT_COMMISSION_RATE),
+    )
+    .unwrap();
+
+    assert!(!swap_result.spread_amount.is_zero());
+    assert!(!swap_result.commission_amount.is_zero());
+    assert_eq!(
+        swap_result.return_amount + swap_result.spread_amount + swap_result.commission_amount,
+        Uint128::from(2_250_000_000u128)
+    );
+}
+
+#[test]
+fn test_compute_offer_amount_round_trip() {
+    // offering the reverse simulated amount returns the asked amount, up to
+    // the rounding of the commission
+    for (offer_pool, ask_pool) in [
+        (30_000_000_000u128, 20_000_000_000u128),
+        (20_000_000_000u128, 30_000_000_000u128),
+        (395_451_850_234u128, 317_000_000u128),
+    ] {
+        for commission_rate in [
+            Decimal::zero(),
+            Decimal::permille(DEFAULT_COMMISSION_RATE),
+            Decimal::percent(1),
+        ] {
+            for ask_amount in [1u128, 12_345u128, 1_000_000u128, ask_pool / 10] {
+                let (offer_amount, _, _) = compute_offer_amount(
+                    Uint128::from(offer_pool),
+                    Uint128::from(ask_pool),
+                    Uint128::from(ask_amount),
+                    commission_rate,
+                )
+                .unwrap();
+                let return_amount = compute_swap(
+                    Uint128::from(offer_pool),
+                    Uint128::from(ask_pool),
+                    offer_amount,
+                    commission_rate,
+                )
+                .unwrap()
+                .return_amount;
+
+                assert!(
+                    return_amount.u128().abs_diff(ask_amount) <= 1,
+                    "asked {} but {} offered returns {}",
+                    ask_amount,
+                    offer_amount,
+                    return_amount
+                );
+            }
+        }
+    }
+}
+
 fn compute_offer_amount(
     offer_pool: Uint128,
     ask_pool: Uint128,
     ask_amount: Uint128,
+    commission_rate: Decimal,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let ask_amount: Uint256 = ask_amount.into();
 
-    let commission_rate = Decimal256::permille(COMMISSION_RATE);
+    let commission_rate: Decimal256 = commission_rate.into();
 
     // ask => offer
     // offer_amount = cp / (ask_pool - ask_amount / (1 - commission_rate)) - offer_pool
@@ -923,6 +1867,14 @@ fn compute_offer_amount(
     ))
 }
 
+pub fn assert_commission_rate(commission_rate: Decimal) -> Result<(), ContractError> {
+    if commission_rate > Decimal::percent(MAX_COMMISSION_RATE) {
+        return Err(ContractError::InvalidCommissionRate {});
+    }
+
+    Ok(())
+}
+
 /// If `belief_price` and `max_spread` both are given,
 /// we compute new spread else we just use terraswap
 /// spread to check `max_spread`
@@ -1033,16 +1985,53 @@ pub fn assert_minimum_assets(
 const TARGET_CONTRACT_VERSION: &str = "0.1.1";
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
-    deps: DepsMut<TerraQuery>,
+    mut deps: DepsMut<TerraQuery>,
     _env: Env,
     _msg: MigrateMsg,
 ) -> Result<Response<TerraMsg>, ContractError> {
+    let prev_version = get_contract_version(deps.storage)?;
+    assert_no_downgrade(&prev_version.version, CONTRACT_VERSION)?;
+
     migrate_version(
-        deps,
+        deps.branch(),
         TARGET_CONTRACT_VERSION,
         CONTRACT_NAME,
         CONTRACT_VERSION,
     )?;
 
+    // only a config stored before the commission settings fails to parse,
+    // a config already migrated is left untouched
+    match CONFIG.load(deps.storage) {
+        Ok(_) => {}
+        Err(StdError::ParseErr { .. }) => {
+            let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+            CONFIG.save(
+                deps.storage,
+                &Config {
+                    team_addr: legacy_config.team_addr,
+                    mint_count: legacy_config.mint_count,
+                    burn_count: legacy_config.burn_count,
+                    lunc_dynamic_mint: legacy_config.lunc_dynamic_mint,
+                    ustc_dynamic_mint: legacy_config.ustc_dynamic_mint,
+                    clsm_addr: legacy_config.clsm_addr,
+                    moon_addr: legacy_config.moon_addr,
+                    commission_rate: Decimal::permille(DEFAULT_COMMISSION_RATE),
+                    protocol_fee_fraction: Decimal::percent(DEFAULT_PROTOCOL_FEE_FRACTION),
+                    minimum_liquidity: Uint128::from(DEFAULT_MINIMUM_LIQUIDITY_AMOUNT),
+                    measure_received: false,
+                },
+            )?;
+
+            // the reserve ledger starts from the pools held before the upgrade
+            if RESERVES.may_load(deps.storage)?.is_none() {
+                let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+                let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+                let pools = query_reserves(deps.as_ref(), &pair_info, contract_addr)?;
+                RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+            }
+        }
+        Err(err) => return Err(err.into()),
+    }
+
     Ok(Response::default())
 }
@@ -18,6 +18,15 @@ pub enum ContractError {
     #[error("Invalid zero amount")]
     InvalidZeroAmount {},
 
+    #[error("Invalid commission rate")]
+    InvalidCommissionRate {},
+
+    #[error("Invalid protocol fee fraction")]
+    InvalidProtocolFeeFraction {},
+
+    #[error("{asset} is reserved by the pair and can't be collected")]
+    ReservedAsset { asset: String },
+
     #[error("No moon contract address")]
     NoMoonContractAddress {},
 
@@ -27,12 +36,42 @@ pub enum ContractError {
     #[error("Asset mismatch")]
     AssetMismatch {},
 
+    #[error("The pair assets must differ")]
+    DuplicateAsset {},
+
+    #[error("Invalid asset {asset}")]
+    InvalidAsset { asset: String },
+
+    #[error("Swap output is too small")]
+    SwapOutputTooSmall {},
+
+    #[error("The pool has no liquidity")]
+    NoLiquidity {},
+
     #[error("Min amount assertion ({min_asset} > {asset})")]
     MinAmountAssertion { min_asset: String, asset: String },
 
     #[error("Max slippage assertion")]
     MaxSlippageAssertion {},
 
+    #[error("Slippage exceeded ({min_lp_tokens} > {share})")]
+    SlippageExceeded {
+        min_lp_tokens: String,
+        share: String,
+    },
+
+    #[error("Minimum liquidity must stay locked ({min_lp_token} > {remaining_lp})")]
+    MinimumLiquidityLocked {
+        min_lp_token: String,
+        remaining_lp: String,
+    },
+
+    #[error("Withdraw exceeds the total share ({lp_amount} > {total_share})")]
+    WithdrawExceedsShare {
+        lp_amount: String,
+        total_share: String,
+    },
+
     #[error("More initial liquidity needed ({min_lp_token} > {given_lp})")]
     MinimumLiquidityAmountError {
         min_lp_token: String,
@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use classic_terraswap::asset::PairInfoRaw;
 use cw_storage_plus::Item;
-use cosmwasm_std::{Addr};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Config {
@@ -13,7 +13,58 @@ pub struct Config {
     pub ustc_dynamic_mint: bool,
     pub clsm_addr: Addr,
     pub moon_addr: Option<Addr>,
+    pub commission_rate: Decimal,
+    pub protocol_fee_fraction: Decimal,
+    pub minimum_liquidity: Uint128,
+    /// Count the token amounts received rather than the stated ones
+    pub measure_received: bool,
+}
+
+/// Config of the pairs deployed before the commission settings, read
+/// from the same key to migrate it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LegacyConfig {
+    pub team_addr: Addr,
+    pub mint_count: u64,
+    pub burn_count: u64,
+    pub lunc_dynamic_mint: bool,
+    pub ustc_dynamic_mint: bool,
+    pub clsm_addr: Addr,
+    pub moon_addr: Option<Addr>,
+}
+
+/// Time weighted sum of the pool prices, price_0 is the price of the
+/// first asset in the second one
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CumulativePrices {
+    pub price_0_cumulative_last: Decimal256,
+    pub price_1_cumulative_last: Decimal256,
+    pub block_time_last: u64,
+}
+
+/// Provision waiting for its token deposits, completed by the reply of the
+/// last transfer once the received amounts are known
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingProvision {
+    pub sender: Addr,
+    pub receiver: String,
+    /// Stated deposits, in the order of the pair assets
+    pub deposits: [Uint128; 2],
+    /// Token balances of the pair before the transfers
+    pub balances_before: [Uint128; 2],
+    pub slippage_tolerance: Option<Decimal>,
+    pub min_lp_tokens: Option<Uint128>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const CUMULATIVE_PRICES: Item<CumulativePrices> = Item::new("cumulative_prices");
+/// Team share of the commissions not claimed yet, in the order of the pair assets
+pub const ACCRUED_FEES: Item<[Uint128; 2]> = Item::new("accrued_fees");
+/// LP reserves recorded by the provisions, withdrawals and swaps, in the
+/// order of the pair assets. Audited against the balances
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
 pub const PAIR_INFO: Item<PairInfoRaw> = Item::new("pair_info");
+/// Team address proposed by the current team, waiting to be accepted
+pub const PENDING_TEAM_ADDR: Item<Addr> = Item::new("pending_team_addr");
+pub const PENDING_PROVISION: Item<PendingProvision> = Item::new("pending_provision");
@@ -1,41 +1,38 @@
 use crate::contract::{
-    assert_max_spread, assert_minimum_assets, execute, instantiate, query_pair_info, query_pool,
-    query_reverse_simulation, query_simulation, reply,
+    assert_max_spread, assert_minimum_assets, compute_initial_share, execute, instantiate, migrate,
+    query_accrued_fees, query_config, query_cumulative_prices, query_pair_info, query_pool,
+    query_reserve_audit, query_reverse_simulation, query_simulate_withdraw, query_simulation,
+    query_simulation_v2, query_spot_price, reply,
 };
 use crate::error::ContractError;
-use classic_bindings::TerraMsg;
+use crate::state::{Config, LegacyConfig, ACCRUED_FEES, CONFIG, LEGACY_CONFIG, RESERVES};
+use classic_bindings::{TerraMsg, TerraQuery};
 use classic_terraswap::mock_querier::mock_dependencies;
+use classic_terraswap::moon::MoonExecuteMsg;
 use std::str::FromStr;
 
 use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
 use classic_terraswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PoolResponse, ReverseSimulationResponse, SimulateWithdrawResponse, SimulationResponse,
+    SimulationResponseV2, SpotPriceResponse,
 };
 use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn, Response, StdError, SubMsg,
-    SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, DepsMut,
+    Reply, ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+    let msg = pair_instantiate_msg(None);
 
     // we can just call .unwrap() to assert this was a success
     let env = mock_env();
@@ -115,18 +112,7 @@ fn provide_liquidity() {
         (&"asset0000".to_string(), &[]),
     ]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+    let msg = pair_instantiate_msg(None);
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -168,6 +154,7 @@ fn provide_liquidity() {
         receiver: None,
         deadline: None,
         slippage_tolerance: None,
+        min_lp_tokens: None,
     };
     let env = mock_env();
     let info = mock_info(
@@ -210,6 +197,7 @@ fn provide_liquidity() {
         receiver: None,
         deadline: None,
         slippage_tolerance: None,
+        min_lp_tokens: None,
     };
 
     let env = mock_env();
@@ -304,6 +292,7 @@ fn provide_liquidity() {
         receiver: Some("staking0000".to_string()), // try changing receiver
         deadline: None,
         slippage_tolerance: Some(Decimal::from_str("0.005").unwrap()),
+        min_lp_tokens: None,
     };
 
     let env = mock_env();
@@ -361,6 +350,7 @@ fn provide_liquidity() {
         receiver: Some("staking0000".to_string()), // try changing receiver
         deadline: None,
         slippage_tolerance: Some(Decimal::from_str("0.05").unwrap()),
+        min_lp_tokens: None,
     };
 
     let env = mock_env();
@@ -421,6 +411,7 @@ fn provide_liquidity() {
         receiver: None,
         deadline: None,
         slippage_tolerance: Some(Decimal::from_str("0.005").unwrap()),
+        min_lp_tokens: None,
     };
 
     let env = mock_env();
@@ -479,6 +470,7 @@ fn provide_liquidity() {
         receiver: None,
         deadline: None,
         slippage_tolerance: Some(Decimal::from_str("0.05").unwrap()),
+        min_lp_tokens: None,
     };
 
     let env = mock_env();
@@ -519,6 +511,100 @@ fn provide_liquidity() {
     );
 }
 
+#[test]
+fn test_provide_liquidity_refunds_excess() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(200u128 + 150u128), /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    // the pool holds 2 uusd per token, so 150uusd against 50 tokens
+    // over-supplies 50uusd
+    let provide_msg = |slippage_tolerance: &str| ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(150u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(50u128),
+            },
+        ],
+        receiver: None,
+        deadline: None,
+        slippage_tolerance: Some(Decimal::from_str(slippage_tolerance).unwrap()),
+        min_lp_tokens: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(150u128),
+        }],
+    );
+
+    // a third of the uusd deposit would be unused
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), provide_msg("0.3")).unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    let res = execute(deps.as_mut(), mock_env(), info, provide_msg("0.34")).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(50u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("refund_assets", "50uusd, 0asset0000")));
+}
+
 #[test]
 fn withdraw_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
@@ -541,17 +627,10 @@ fn withdraw_liquidity() {
         ),
     ]);
 
+    // the whole supply is withdrawn, so none of it is kept locked
     let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
+        minimum_liquidity: Some(Uint128::zero()),
+        ..pair_instantiate_msg(None)
     };
 
     let env = mock_env();
@@ -709,7 +788,12 @@ fn failed_reply_with_unknown_id() {
         },
     );
 
-    assert_eq!(res, Err(StdError::generic_err("invalid reply msg")))
+    assert_eq!(
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "invalid reply msg"
+        )))
+    )
 }
 
 #[test]
@@ -741,17 +825,11 @@ fn try_native_to_token() {
         ),
     ]);
 
+    // the team share of the commission would leave the pool the
+    // simulation below runs against
     let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
+        protocol_fee_fraction: Some(Decimal::zero()),
+        ..pair_instantiate_msg(Some(Decimal::permille(3)))
     };
 
     let env = mock_env();
@@ -877,6 +955,7 @@ fn try_native_to_token() {
             attr("tax_amount", expected_tax_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("schema_version", "1"),
         ]
     );
 
@@ -925,16 +1004,9 @@ fn try_token_to_native() {
     ]);
 
     let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
         asset_decimals: [8u8, 8u8],
+        protocol_fee_fraction: Some(Decimal::zero()),
+        ..pair_instantiate_msg(Some(Decimal::permille(3)))
     };
 
     let env = mock_env();
@@ -988,6 +1060,7 @@ fn try_token_to_native() {
             max_spread: None,
             to: None,
             deadline: None,
+            minimum_receive: None,
         })
         .unwrap(),
     });
@@ -1085,6 +1158,7 @@ fn try_token_to_native() {
             attr("tax_amount", expected_tax_amount.to_string()),
             attr("spread_amount", expected_spread_amount.to_string()),
             attr("commission_amount", expected_commission_amount.to_string()),
+            attr("schema_version", "1"),
         ]
     );
 
@@ -1110,6 +1184,7 @@ fn try_token_to_native() {
             max_spread: None,
             to: None,
             deadline: None,
+            minimum_receive: None,
         })
         .unwrap(),
     });
@@ -1345,18 +1420,7 @@ fn test_query_pool() {
         ),
     ]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+    let msg = pair_instantiate_msg(None);
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -1680,3 +1744,2206 @@ fn test_assert_minimum_assets_with_unknown_asset() {
         }
     )
 }
+
+fn pair_instantiate_msg(commission_rate: Option<Decimal>) -> InstantiateMsg {
+    InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate,
+        protocol_fee_fraction: None,
+        minimum_liquidity: None,
+        measure_received: None,
+    }
+}
+
+fn store_liquidity_token(deps: DepsMut<TerraQuery>) {
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    reply(deps, mock_env(), reply_msg).unwrap();
+}
+
+#[test]
+fn test_default_commission_rate() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.commission_rate, Decimal::permille(2));
+}
+
+#[test]
+fn test_query_config() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    assert_eq!(
+        query_config(deps.as_ref()).unwrap(),
+        ConfigResponse {
+            team_addr: "team0000".to_string(),
+            mint_count: 1,
+            burn_count: 1,
+            lunc_dynamic_mint: false,
+            ustc_dynamic_mint: false,
+            clsm_addr: "clsm0000".to_string(),
+            moon_addr: None,
+            commission_rate: Decimal::permille(2),
+            protocol_fee_fraction: Decimal::percent(50),
+            minimum_liquidity: Uint128::from(1_000u128),
+            measure_received: false,
+        }
+    );
+
+    // a custom minimum liquidity round-trips
+    let msg = InstantiateMsg {
+        minimum_liquidity: Some(Uint128::from(5_000u128)),
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config = query_config(deps.as_ref()).unwrap();
+    assert_eq!(config.minimum_liquidity, Uint128::from(5_000u128));
+}
+
+#[test]
+fn test_invalid_commission_rate() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(Some(Decimal::percent(11)));
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommissionRate {});
+
+    // the max commission rate is accepted
+    let msg = pair_instantiate_msg(Some(Decimal::percent(10)));
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn test_instantiate_duplicate_asset() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::DuplicateAsset {});
+}
+
+#[test]
+fn test_instantiate_token_info() {
+    let mut deps = mock_dependencies(&[]);
+
+    // asset0000 is not a token
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidAsset {
+            asset: "asset0000".to_string(),
+        }
+    );
+
+    // the decimals reported by the token replace the given ones
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+    let msg = InstantiateMsg {
+        asset_decimals: [6u8, 6u8],
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(pair_info.asset_decimals, [6u8, 8u8]);
+}
+
+#[test]
+fn test_query_simulation_v2() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000_000_000_000_000u128),
+        )],
+    )]);
+
+    // the token reports 8 decimals
+    let msg = InstantiateMsg {
+        asset_decimals: [6u8, 8u8],
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let uusd_offer = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(1_000u128),
+    };
+    let simulation = query_simulation(deps.as_ref(), uusd_offer.clone()).unwrap();
+    assert_eq!(
+        query_simulation_v2(deps.as_ref(), uusd_offer).unwrap(),
+        SimulationResponseV2 {
+            return_amount: simulation.return_amount,
+            spread_amount: simulation.spread_amount,
+            commission_amount: simulation.commission_amount,
+            offer_decimals: 6u8,
+            ask_decimals: 8u8,
+        }
+    );
+
+    // the decimals follow the offered asset
+    let token_offer = Asset {
+        info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        amount: Uint128::from(1_000_000_000_000u128),
+    };
+    let simulation = query_simulation(deps.as_ref(), token_offer.clone()).unwrap();
+    assert_eq!(
+        query_simulation_v2(deps.as_ref(), token_offer).unwrap(),
+        SimulationResponseV2 {
+            return_amount: simulation.return_amount,
+            spread_amount: simulation.spread_amount,
+            commission_amount: simulation.commission_amount,
+            offer_decimals: 8u8,
+            ask_decimals: 6u8,
+        }
+    );
+}
+
+#[test]
+fn test_protocol_fee_fraction() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    // the team takes half of the commission by default
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.protocol_fee_fraction, Decimal::percent(50));
+
+    // more than the whole commission is rejected
+    let msg = InstantiateMsg {
+        protocol_fee_fraction: Some(Decimal::percent(101)),
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidProtocolFeeFraction {});
+
+    let msg = InstantiateMsg {
+        protocol_fee_fraction: Some(Decimal::percent(30)),
+        ..pair_instantiate_msg(Some(Decimal::percent(1)))
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 1000 uusd returns 999 asset0000 before the 1% commission, which
+    // rounds up to 10, and 30% of it accrues to the team
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("commission_amount", "10")));
+    assert!(res
+        .messages
+        .contains(&SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(989u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))));
+
+    let res = query_accrued_fees(deps.as_ref()).unwrap();
+    assert_eq!(res.accrued_fees[1].amount, Uint128::from(3u128));
+}
+
+#[test]
+fn test_accrued_fees() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = InstantiateMsg {
+        protocol_fee_fraction: Some(Decimal::percent(30)),
+        ..pair_instantiate_msg(Some(Decimal::percent(1)))
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // each swap commission of 10 accrues 3 to the team
+    for _ in 0..2 {
+        let msg = ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(1_000u128),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1_000u128),
+            }],
+        );
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("commission_amount", "10")));
+    }
+
+    let res = query_accrued_fees(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.accrued_fees,
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::zero(),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(6u128),
+            },
+        ]
+    );
+
+    // the accrued fees are not part of the LP reserves
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, Uint128::from(999_994u128));
+
+    // only the team can claim
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimFees {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("team0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimFees {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "team0000".to_string(),
+                amount: Uint128::from(6u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query_accrued_fees(deps.as_ref()).unwrap();
+    assert!(res.accrued_fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // nothing is left to claim
+    let info = mock_info("team0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::ClaimFees {}).unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
+}
+
+#[test]
+fn test_simulation_with_custom_commission_rate() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(Some(Decimal::percent(1)));
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1_000u128),
+        },
+    )
+    .unwrap();
+
+    // return before commission: 1_000_000 * 1_000 / 1_001_000 = 999
+    // commission: ceil(999 * 1%) = 10
+    assert_eq!(
+        res,
+        SimulationResponse {
+            return_amount: Uint128::from(989u128),
+            spread_amount: Uint128::from(1u128),
+            commission_amount: Uint128::from(10u128),
+        }
+    );
+}
+
+#[test]
+fn test_update_commission() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the team can update the commission
+    let msg = ExecuteMsg::UpdateCommission {
+        commission_rate: Decimal::permille(5),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // out of range rate is rejected
+    let msg = ExecuteMsg::UpdateCommission {
+        commission_rate: Decimal::percent(20),
+    };
+    let info = mock_info("team0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommissionRate {});
+
+    let msg = ExecuteMsg::UpdateCommission {
+        commission_rate: Decimal::permille(5),
+    };
+    let info = mock_info("team0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_commission"),
+            attr("old_commission_rate", "0.002"),
+            attr("new_commission_rate", "0.005"),
+        ]
+    );
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.commission_rate, Decimal::permille(5));
+}
+
+#[test]
+fn test_migrate() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let pair_info = query_pair_info(deps.as_ref()).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+
+    // a newer version can't be downgraded
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-pair", "9.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(format!(
+            "cannot downgrade from 9.0.0 to {}",
+            env!("CARGO_PKG_VERSION")
+        )))
+    );
+
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-pair", "0.1.1").unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // state is left untouched
+    assert_eq!(query_pair_info(deps.as_ref()).unwrap(), pair_info);
+    assert_eq!(CONFIG.load(deps.as_ref().storage).unwrap(), config);
+}
+
+#[test]
+fn test_migrate_legacy_config() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000u128),
+    }]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2_000u128))],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // a pair deployed before the commission settings
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                team_addr: Addr::unchecked("team0000"),
+                mint_count: 3,
+                burn_count: 2,
+                lunc_dynamic_mint: true,
+                ustc_dynamic_mint: false,
+                clsm_addr: Addr::unchecked("clsm0000"),
+                moon_addr: Some(Addr::unchecked("moon0000")),
+            },
+        )
+        .unwrap();
+    CONFIG.load(deps.as_ref().storage).unwrap_err();
+
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-pair", "0.1.1").unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            team_addr: Addr::unchecked("team0000"),
+            mint_count: 3,
+            burn_count: 2,
+            lunc_dynamic_mint: true,
+            ustc_dynamic_mint: false,
+            clsm_addr: Addr::unchecked("clsm0000"),
+            moon_addr: Some(Addr::unchecked("moon0000")),
+            commission_rate: Decimal::permille(2),
+            protocol_fee_fraction: Decimal::percent(50),
+            minimum_liquidity: Uint128::from(1_000u128),
+            measure_received: false,
+        }
+    );
+
+    // the reserve ledger is seeded from the pools
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_000u128), Uint128::from(2_000u128)]
+    );
+
+    // running the migration again keeps the migrated config
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(CONFIG.load(deps.as_ref().storage).unwrap(), config);
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn test_propose_accept_admin() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the current team can propose an address
+    let msg = ExecuteMsg::ProposeAdmin {
+        new: "team0001".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // malformed address is rejected
+    let msg = ExecuteMsg::ProposeAdmin {
+        new: "T".to_string(),
+    };
+    let info = mock_info("team0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    match err {
+        ContractError::Std(StdError::GenericErr { .. }) => {}
+        _ => panic!("Must return generic error"),
+    }
+
+    // nothing to accept before a proposal
+    let info = mock_info("team0001", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::ProposeAdmin {
+        new: "team0001".to_string(),
+    };
+    let info = mock_info("team0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_admin"),
+            attr("pending_team_addr", "team0001"),
+        ]
+    );
+
+    // the current team keeps control until the proposal is accepted
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.team_addr.as_str(), "team0000");
+
+    let info = mock_info("team0000", &[]);
+    let msg = ExecuteMsg::UpdateCommission {
+        commission_rate: Decimal::permille(3),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("team0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "accept_admin"),
+            attr("old_team_addr", "team0000"),
+            attr("new_team_addr", "team0001"),
+        ]
+    );
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.team_addr.as_str(), "team0001");
+
+    // the proposal is consumed
+    let info = mock_info("team0001", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_accept_admin_wrong_acceptor() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ProposeAdmin {
+        new: "team0001".to_string(),
+    };
+    let info = mock_info("team0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // neither a stranger nor the current team can accept for the proposed address
+    for sender in ["addr0000", "team0000"] {
+        let info = mock_info(sender, &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.team_addr.as_str(), "team0000");
+}
+
+#[test]
+fn test_collect_dust() {
+    let mut deps = mock_dependencies(&[
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        },
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(500u128),
+        },
+    ]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128))],
+        ),
+        (
+            &"clsm0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(50u128))],
+        ),
+        (
+            &"stray0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(300u128))],
+        ),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let msg = ExecuteMsg::CollectDust {
+        assets: vec![
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "stray0000".to_string(),
+            },
+        ],
+    };
+
+    // only the team can collect
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("team0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "team0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(500u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "stray0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "team0000".to_string(),
+                    amount: Uint128::from(300u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "collect_dust"),
+            attr("collected", "500uluna, 300stray0000"),
+        ]
+    );
+
+    // the pool assets, the LP token and CLSM stay in the pair
+    for asset_info in [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "liquidity0000".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "clsm0000".to_string(),
+        },
+    ] {
+        let msg = ExecuteMsg::CollectDust {
+            assets: vec![asset_info.clone()],
+        };
+        let info = mock_info("team0000", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReservedAsset {
+                asset: asset_info.to_string(),
+            }
+        );
+    }
+}
+
+#[test]
+fn test_query_spot_price() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2_000u128),
+    }]);
+
+    // the token side of the pool is empty
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: SpotPriceResponse = query_spot_price(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        SpotPriceResponse {
+            asset_0_per_1: Decimal::zero(),
+            asset_1_per_0: Decimal::zero(),
+        }
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(500u128))],
+    )]);
+
+    let res: SpotPriceResponse = query_spot_price(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        SpotPriceResponse {
+            asset_0_per_1: Decimal::from_ratio(4u128, 1u128),
+            asset_1_per_0: Decimal::percent(25),
+        }
+    );
+}
+
+#[test]
+fn test_provide_liquidity_min_lp_tokens() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let provide_msg = |min_lp_tokens: Option<Uint128>| ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(2_000u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(2_000u128),
+            },
+        ],
+        receiver: None,
+        deadline: None,
+        slippage_tolerance: None,
+        min_lp_tokens,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(2_000u128),
+        }],
+    );
+
+    // sqrt(2_000 * 2_000) - MINIMUM_LIQUIDITY_AMOUNT = 1_000
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        provide_msg(Some(Uint128::from(1_001u128))),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SlippageExceeded {
+            min_lp_tokens: "1001".to_string(),
+            share: "1000".to_string(),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        provide_msg(Some(Uint128::from(1_000u128))),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("share", "1000")));
+}
+
+#[test]
+fn test_compute_initial_share() {
+    let minimum_liquidity = Uint128::from(1_000u128);
+
+    // sqrt(1_000 * 4_000) - 1_000
+    assert_eq!(
+        compute_initial_share(
+            [Uint128::from(1_000u128), Uint128::from(4_000u128)],
+            minimum_liquidity
+        )
+        .unwrap(),
+        Uint128::from(1_000u128)
+    );
+
+    // the geometric mean rounds down, sqrt(2_000_000) = 1_414.2
+    assert_eq!(
+        compute_initial_share(
+            [Uint128::from(10u128), Uint128::from(200_000u128)],
+            minimum_liquidity
+        )
+        .unwrap(),
+        Uint128::from(414u128)
+    );
+
+    // exactly the minimum leaves nothing for the provider
+    assert_eq!(
+        compute_initial_share(
+            [Uint128::from(1_000u128), Uint128::from(1_000u128)],
+            minimum_liquidity
+        )
+        .unwrap(),
+        Uint128::zero()
+    );
+
+    let err = compute_initial_share(
+        [Uint128::from(999u128), Uint128::from(999u128)],
+        minimum_liquidity,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinimumLiquidityAmountError {
+            min_lp_token: "1000".to_string(),
+            given_lp: "999".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_initial_provision_lock() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(4_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(1_000u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(4_000u128),
+            },
+        ],
+        receiver: None,
+        deadline: None,
+        slippage_tolerance: None,
+        min_lp_tokens: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(4_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the minimum liquidity is minted to the pair itself, the rest of
+    // sqrt(1_000 * 4_000) to the provider
+    let mint_msg = |recipient: &str, amount: u128| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    };
+    assert_eq!(res.messages[0], mint_msg(MOCK_CONTRACT_ADDR, 1_000));
+    assert_eq!(res.messages.last().unwrap(), &mint_msg("addr0000", 1_000));
+    assert!(res.attributes.contains(&attr("share", "1000")));
+}
+
+#[test]
+fn test_swap_deadline() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let swap_msg = |deadline: u64| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: Some(deadline),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+
+    // the deadline has already passed
+    let env = mock_env();
+    let deadline = env.block.time.seconds();
+    let err = execute(deps.as_mut(), env, info.clone(), swap_msg(deadline)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Expired deadline"))
+    );
+
+    let env = mock_env();
+    let deadline = env.block.time.seconds() + 100;
+    let res = execute(deps.as_mut(), env, info, swap_msg(deadline)).unwrap();
+    assert!(res.attributes.contains(&attr("action", "swap")));
+}
+
+#[test]
+fn test_reserve_audit() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+
+    // 500 asset0000 were sent to the pair outside of a provision
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_500u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    let res = query_reserve_audit(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.balances,
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(1_000_000u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(1_000_500u128),
+            },
+        ]
+    );
+    assert_eq!(res.reserves[1].amount, Uint128::from(1_000_000u128));
+    assert_eq!(res.surplus, [Uint128::zero(), Uint128::from(500u128)]);
+    assert_eq!(res.shortfall, [Uint128::zero(), Uint128::zero()]);
+
+    // the accrued fees are expected in the balances
+    ACCRUED_FEES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::from(500u128)],
+        )
+        .unwrap();
+    let res = query_reserve_audit(deps.as_ref()).unwrap();
+    assert_eq!(res.surplus, [Uint128::zero(), Uint128::zero()]);
+    assert_eq!(res.shortfall, [Uint128::from(100u128), Uint128::zero()]);
+}
+
+#[test]
+fn test_sync_reserves() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(900_000u128),
+    }]);
+
+    // 500 asset0000 were donated, uusd is short of the stored reserve
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_500u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(1_000_000u128), Uint128::from(1_000_000u128)],
+        )
+        .unwrap();
+
+    // only the team can sync
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::SyncReserves {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the donation is recorded in the ledger, the shortfall doesn't lower uusd
+    let pool = query_pool(deps.as_ref()).unwrap();
+    let info = mock_info("team0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::SyncReserves {}).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("reserves", "1000000, 1000500")));
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(1_000_000u128), Uint128::from(1_000_500u128)]
+    );
+
+    let res = query_reserve_audit(deps.as_ref()).unwrap();
+    assert_eq!(res.surplus, [Uint128::zero(), Uint128::zero()]);
+    assert_eq!(res.shortfall, [Uint128::from(100_000u128), Uint128::zero()]);
+
+    // the pool priced by the swaps already held the donation
+    assert_eq!(query_pool(deps.as_ref()).unwrap(), pool);
+    assert_eq!(pool.assets[1].amount, Uint128::from(1_000_500u128));
+}
+
+#[test]
+fn test_swap_to_recipient() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the return goes to `to` when it is given, to the sender otherwise
+    for (to, receiver) in [(Some("addr0001"), "addr0001"), (None, "addr0000")] {
+        let msg = ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(1_000u128),
+            },
+            belief_price: None,
+            max_spread: None,
+            to: to.map(|to| to.to_string()),
+            deadline: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1_000u128),
+            }],
+        );
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("receiver", receiver)));
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!(contract_addr, "asset0000");
+                match from_binary(msg).unwrap() {
+                    Cw20ExecuteMsg::Transfer { recipient, .. } => {
+                        assert_eq!(recipient, receiver)
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+#[test]
+fn test_swap_with_failing_moon_call() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let swap = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+
+    // both periods are due, the moon calls are sent as submessages
+    // replied to on error only
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), swap.clone()).unwrap();
+    assert_eq!(
+        res.messages[1..],
+        [
+            SubMsg {
+                id: 3,
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "moon0000".to_string(),
+                    msg: to_binary(&MoonExecuteMsg::VestingMint { to: None }).unwrap(),
+                    funds: vec![],
+                }),
+                gas_limit: None,
+                reply_on: ReplyOn::Error,
+            },
+            SubMsg {
+                id: 4,
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "moon0000".to_string(),
+                    msg: to_binary(&MoonExecuteMsg::AutomaticBurn {}).unwrap(),
+                    funds: vec![],
+                }),
+                gas_limit: None,
+                reply_on: ReplyOn::Error,
+            },
+        ]
+    );
+
+    // the moon rejects the burn, the reply keeps the swap
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 4,
+            result: SubMsgResult::Err("Unauthorized".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "moon_call_failed"),
+            attr("call", "automatic_burn"),
+            attr("error", "Unauthorized")
+        ]
+    );
+
+    // the failed burn is sent again by the next swap, the vesting is not
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), swap.clone()).unwrap();
+    assert_eq!(
+        res.messages[1..],
+        [SubMsg {
+            id: 4,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "moon0000".to_string(),
+                msg: to_binary(&MoonExecuteMsg::AutomaticBurn {}).unwrap(),
+                funds: vec![],
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Error,
+        }]
+    );
+
+    // once it goes through nothing more is sent in the same period
+    let res = execute(deps.as_mut(), mock_env(), info, swap).unwrap();
+    assert_eq!(res.messages.len(), 1);
+}
+
+#[test]
+fn test_native_swap_funds() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let swap_msg = |info: AssetInfo| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info,
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: Some("addr0001".to_string()),
+        deadline: None,
+    };
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let mismatch = ContractError::Std(StdError::generic_err(
+        "Native token balance mismatch between the argument and the transferred",
+    ));
+
+    // the sent funds fall short of the claimed offer
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(999u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, swap_msg(uusd.clone())).unwrap_err();
+    assert_eq!(err, mismatch);
+
+    // nothing was sent for the offered denom
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, swap_msg(uusd.clone())).unwrap_err();
+    assert_eq!(err, mismatch);
+
+    // the native asset is not one of the pool assets
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let msg = swap_msg(AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    });
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    // token offers go through the receive hook
+    let info = mock_info("addr0000", &[]);
+    let msg = swap_msg(AssetInfo::Token {
+        contract_addr: "asset0000".to_string(),
+    });
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // matching funds swap and pay the return to the given receiver
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(uusd)).unwrap();
+    assert!(res.attributes.contains(&attr("receiver", "addr0001")));
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) => {
+            assert_eq!(contract_addr, "asset0000");
+            match from_binary(msg).unwrap() {
+                Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                    assert_eq!(recipient, "addr0001");
+                    assert!(!amount.is_zero());
+                }
+                _ => panic!("unexpected cw20 message"),
+            }
+        }
+        _ => panic!("unexpected message"),
+    }
+}
+
+#[test]
+fn test_withdraw_liquidity_proportional() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[
+                (&"addr0000".to_string(), &Uint128::from(99_000u128)),
+                (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128)),
+            ],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200_000u128))],
+        ),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let withdraw_msg = |amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    // half of the LP supply returns half of each reserve
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, withdraw_msg(50_000)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(50_000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(100_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(50_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // the locked minimum liquidity can not be withdrawn
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, withdraw_msg(99_001)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinimumLiquidityLocked {
+            min_lp_token: "1000".to_string(),
+            remaining_lp: "999".to_string(),
+        }
+    );
+
+    let info = mock_info("liquidity0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, withdraw_msg(99_000)).unwrap();
+}
+
+#[test]
+fn test_simulate_withdraw() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[
+                (&"addr0000".to_string(), &Uint128::from(99_000u128)),
+                (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128)),
+            ],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200_000u128))],
+        ),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let res = query_simulate_withdraw(deps.as_ref(), Uint128::from(30_000u128)).unwrap();
+    assert_eq!(
+        res,
+        SimulateWithdrawResponse {
+            asset_0: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(30_000u128),
+            },
+            asset_1: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(60_000u128),
+            },
+        }
+    );
+
+    // the real withdraw refunds the simulated amounts
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(30_000u128),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res_withdraw = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res_withdraw.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: res.asset_0.amount,
+            }],
+        }))
+    );
+    assert_eq!(
+        res_withdraw.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: res.asset_1.amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+
+    // more than the LP supply can not be withdrawn
+    let err = query_simulate_withdraw(deps.as_ref(), Uint128::from(100_001u128)).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WithdrawExceedsShare {
+            lp_amount: "100001".to_string(),
+            total_share: "100000".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_query_pool_total_share() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (&"asset0000".to_string(), &[]),
+    ]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // no LP token has been created yet
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.total_share, Uint128::zero());
+
+    store_liquidity_token(deps.as_mut());
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.total_share, Uint128::zero());
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(2_000u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(2_000u128),
+            },
+        ],
+        receiver: None,
+        deadline: None,
+        slippage_tolerance: None,
+        min_lp_tokens: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(2_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let minted: Uint128 = res
+        .messages
+        .iter()
+        .filter_map(|sub_msg| match &sub_msg.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) if contract_addr == "liquidity0000" => match from_binary(msg).unwrap() {
+                Cw20ExecuteMsg::Mint { amount, .. } => Some(amount),
+                _ => None,
+            },
+            _ => None,
+        })
+        .sum();
+    assert_eq!(minted, Uint128::from(2_000u128));
+
+    // the mints ran and the deposits reached the pool
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[
+                (&"addr0000".to_string(), &Uint128::from(1_000u128)),
+                (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128)),
+            ],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2_000u128))],
+        ),
+    ]);
+
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.total_share, minted);
+}
+
+#[test]
+fn test_swap_max_spread() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_100_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 100000 uusd would return 100000 at the spot price but executes at 90909,
+    // a spread of roughly 9%
+    let swap_msg = |max_spread: Decimal| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100_000u128),
+        },
+        belief_price: None,
+        max_spread: Some(max_spread),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_000u128),
+        }],
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(Decimal::percent(5)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        swap_msg(Decimal::percent(10)),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("spread_amount", "9091")));
+}
+
+#[test]
+fn test_swap_no_liquidity() {
+    // only the offered amount sits in the freshly instantiated pair
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100_000u128),
+    }]);
+
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::NoLiquidity {});
+}
+
+#[test]
+fn test_swap_output_too_small() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000_000_001u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 1 uusd is worth less than 1 asset0000 at this pool price
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::SwapOutputTooSmall {});
+}
+
+#[test]
+fn test_cw20_swap_minimum_receive() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_001_000u128),
+        )],
+    )]);
+
+    let msg = pair_instantiate_msg(None);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 1000 asset0000 returns 999 uusd, 997 after the 0.2% commission
+    let swap_msg = |minimum_receive: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(1_000u128),
+            msg: to_binary(&Cw20HookMsg::Swap {
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+                minimum_receive: Some(Uint128::from(minimum_receive)),
+            })
+            .unwrap(),
+        })
+    };
+
+    let info = mock_info("asset0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, swap_msg(998)).unwrap_err();
+    assert_eq!(err, ContractError::MaxSpreadAssertion {});
+
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(997)).unwrap();
+    assert!(res.attributes.contains(&attr("return_amount", "997")));
+}
+
+#[test]
+fn test_cumulative_prices() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1_001_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000u128),
+        )],
+    )]);
+
+    // the accrued team fees would leave the reserves the prices are
+    // checked against
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        protocol_fee_fraction: Some(Decimal::zero()),
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000u128),
+        }],
+    );
+
+    // both prices are 1 for the first 100 seconds
+    let mut swap_env = env.clone();
+    swap_env.block.time = env.block.time.plus_seconds(100);
+    execute(deps.as_mut(), swap_env, info.clone(), swap_msg.clone()).unwrap();
+
+    let res: CumulativePricesResponse = query_cumulative_prices(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        CumulativePricesResponse {
+            price_0_cumulative_last: Decimal256::from_ratio(100u128, 1u128),
+            price_1_cumulative_last: Decimal256::from_ratio(100u128, 1u128),
+            block_time_last: env.block.time.seconds() + 100,
+        }
+    );
+
+    // the token reserve halves, for the next 200 seconds
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(500_000u128))],
+    )]);
+
+    let mut swap_env = env.clone();
+    swap_env.block.time = env.block.time.plus_seconds(300);
+    execute(deps.as_mut(), swap_env, info, swap_msg).unwrap();
+
+    let res: CumulativePricesResponse = query_cumulative_prices(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        CumulativePricesResponse {
+            price_0_cumulative_last: Decimal256::from_ratio(200u128, 1u128),
+            price_1_cumulative_last: Decimal256::from_ratio(500u128, 1u128),
+            block_time_last: env.block.time.seconds() + 300,
+        }
+    );
+}
+
+#[test]
+fn test_measure_received() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(4_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        measure_received: Some(true),
+        ..pair_instantiate_msg(None)
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut());
+
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the token deposit is pulled first, the provision waits for the reply
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(1_000u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(4_000u128),
+            },
+        ],
+        receiver: None,
+        deadline: None,
+        slippage_tolerance: None,
+        min_lp_tokens: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(4_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 2,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(1_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+        }]
+    );
+
+    // the token takes 1% on transfer, so the pair receives 990
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(990u128))],
+        ),
+    ]);
+    let reply_msg = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // sqrt(990 * 4_000) less the minimum liquidity
+    assert!(res.attributes.contains(&attr("share", "989")));
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(989u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::from(4_000u128), Uint128::from(990u128)]
+    );
+
+    // a swap stating 100 only offers the 99 received
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1_089u128))],
+        ),
+    ]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+            minimum_receive: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("offer_amount", "99")));
+}
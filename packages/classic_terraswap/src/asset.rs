@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::moon::{Bucket, BurnSource, PriceBand};
 use crate::querier::{query_balance, query_native_decimals, query_token_balance, query_token_info};
 use classic_bindings::{TerraMsg, TerraQuerier, TerraQuery};
 use cosmwasm_std::{
@@ -24,6 +25,44 @@ impl fmt::Display for Asset {
 
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
+/// Checks the funds hold exactly the native amount of each asset, and no
+/// denom which is not one of the native assets
+pub fn assert_sent_native_token_balances(
+    assets: &[Asset],
+    message_info: &MessageInfo,
+) -> StdResult<()> {
+    for asset in assets.iter() {
+        if let AssetInfo::NativeToken { denom } = &asset.info {
+            let sent = message_info
+                .funds
+                .iter()
+                .find(|coin| coin.denom == *denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+            if sent != asset.amount {
+                return Err(StdError::generic_err(
+                    "Native token balance mismatch between the argument and the transferred",
+                ));
+            }
+        }
+    }
+
+    for coin in message_info.funds.iter() {
+        let expected = assets.iter().any(|asset| match &asset.info {
+            AssetInfo::NativeToken { denom } => *denom == coin.denom,
+            AssetInfo::Token { .. } => false,
+        });
+        if !expected {
+            return Err(StdError::generic_err(format!(
+                "Unexpected native token sent: {}",
+                coin.denom
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 impl Asset {
     pub fn is_native_token(&self) -> bool {
         self.info.is_native_token()
@@ -90,27 +129,10 @@ impl Asset {
         Ok(SubMsg::new(self.into_msg(querier, recipient)?))
     }
 
+    /// Checks the funds hold exactly the native amount of the asset and
+    /// nothing else, so no funds at all for a token asset
     pub fn assert_sent_native_token_balance(&self, message_info: &MessageInfo) -> StdResult<()> {
-        if let AssetInfo::NativeToken { denom } = &self.info {
-            match message_info.funds.iter().find(|x| x.denom == *denom) {
-                Some(coin) => {
-                    if self.amount == coin.amount {
-                        Ok(())
-                    } else {
-                        Err(StdError::generic_err("Native token balance mismatch between the argument and the transferred"))
-                    }
-                }
-                None => {
-                    if self.amount.is_zero() {
-                        Ok(())
-                    } else {
-                        Err(StdError::generic_err("Native token balance mismatch between the argument and the transferred"))
-                    }
-                }
-            }
-        } else {
-            Ok(())
-        }
+        assert_sent_native_token_balances(std::slice::from_ref(self), message_info)
     }
 
     pub fn to_raw(&self, api: &dyn Api) -> StdResult<AssetRaw> {
@@ -182,6 +204,12 @@ impl AssetInfo {
         }
     }
 
+    /// Same asset with the token address in its canonical form, so that the
+    /// addresses differing only in case compare equal
+    pub fn to_normalized(&self, api: &dyn Api) -> StdResult<AssetInfo> {
+        self.to_raw(api)?.to_normal(api)
+    }
+
     pub fn equal(&self, asset: &AssetInfo) -> bool {
         match self {
             AssetInfo::Token { contract_addr, .. } => {
@@ -344,11 +372,34 @@ pub struct VestInfo {
     pub monthly_amount: Uint128,
     pub month_count: Uint128,
     pub month_index: Uint128,
+    /// Asset vested by the bucket, CLSM when it is not set
+    pub asset_info: Option<AssetInfo>,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct MoonInfo {
+    pub clsm_addr: Addr,
+    pub authorized: Vec<Addr>,
+    pub lunc_recipient: Addr,
+    pub minter_addr: Addr,
+    pub lunc_mint_ratio: Decimal,
+    pub ustc_mint_ratio: Decimal,
+    pub max_mint_ratio: Decimal,
+    pub max_mint_amount: Uint128,
+    pub mint_price_band: Option<PriceBand>,
+    pub max_supply: Uint128,
+    pub total_minted: Uint128,
+    pub allow_partial: bool,
+    pub interval_seconds: u64,
+    pub reserve_balance: Uint128,
+    pub max_owed_months: Uint128,
+    pub treasury_addr: Addr,
+    pub burn_threshold: Uint128,
+    pub high_burn_divisor: Uint128,
+    pub low_burn_divisor: Uint128,
+    pub burn_source: BurnSource,
+    pub paused: bool,
     pub pair_vest: VestInfo,
     pub nft_vest: VestInfo,
     pub marketing_vest: VestInfo,
@@ -362,14 +413,132 @@ pub struct VestInfoRaw {
     pub monthly_amount: Uint128,
     pub month_count: Uint128,
     pub month_index: Uint128,
+    pub last_emission_time: u64,
+    /// Amount the partial emissions fell short of the monthly amount
+    pub shortfall: Uint128,
+    /// Months missed for lack of funds, released by a later emission
+    pub months_owed: Uint128,
+    /// Balance registered for the bucket alone, zero means the bucket draws
+    /// on the balance not registered for another bucket
+    pub funding: Uint128,
+    pub asset_info: Option<AssetInfoRaw>,
+}
+
+impl VestInfoRaw {
+    pub fn to_normal(&self, api: &dyn Api) -> StdResult<VestInfo> {
+        Ok(VestInfo {
+            address: api.addr_humanize(&self.address)?.to_string(),
+            monthly_amount: self.monthly_amount,
+            month_count: self.month_count,
+            month_index: self.month_index,
+            asset_info: self
+                .asset_info
+                .as_ref()
+                .map(|asset_info| asset_info.to_normal(api))
+                .transpose()?,
+        })
+    }
+
+    /// Amount still to be emitted
+    pub fn remaining_amount(&self) -> StdResult<Uint128> {
+        Ok(self
+            .month_count
+            .saturating_sub(self.month_index)
+            .checked_mul(self.monthly_amount)?)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct MoonInfoRaw {
     pub clsm_addr: CanonicalAddr,
+    /// Addresses allowed to trigger the scheduled operations
+    pub authorized: Vec<CanonicalAddr>,
+    pub lunc_recipient: CanonicalAddr,
+    pub minter_addr: CanonicalAddr,
+    pub lunc_mint_ratio: Decimal,
+    pub ustc_mint_ratio: Decimal,
+    /// Highest mint ratio the updates can set
+    pub max_mint_ratio: Decimal,
+    pub max_mint_amount: Uint128,
+    /// Pool prices the dynamic mints accept
+    pub mint_price_band: Option<PriceBand>,
+    pub max_supply: Uint128,
+    /// CLSM created by the dynamic mints so far
+    pub total_minted: Uint128,
+    pub allow_partial: bool,
+    /// Minimum time between two emissions of a bucket, the length of a
+    /// month of the vesting schedules
+    pub interval_seconds: u64,
+    /// CLSM balance the emissions can't draw below
+    pub reserve_balance: Uint128,
+    /// Most months an underfunded bucket can carry forward
+    pub max_owed_months: Uint128,
+    pub treasury_addr: CanonicalAddr,
+    pub burn_threshold: Uint128,
+    pub high_burn_divisor: Uint128,
+    pub low_burn_divisor: Uint128,
+    pub burn_source: BurnSource,
+    pub paused: bool,
     pub pair_vest: VestInfoRaw,
     pub nft_vest: VestInfoRaw,
     pub marketing_vest: VestInfoRaw,
     pub game_vest: VestInfoRaw,
     pub team_vest: VestInfoRaw,
 }
+
+impl MoonInfoRaw {
+    pub fn to_normal(&self, api: &dyn Api) -> StdResult<MoonInfo> {
+        Ok(MoonInfo {
+            clsm_addr: api.addr_humanize(&self.clsm_addr)?,
+            authorized: self
+                .authorized
+                .iter()
+                .map(|addr| api.addr_humanize(addr))
+                .collect::<StdResult<Vec<Addr>>>()?,
+            lunc_recipient: api.addr_humanize(&self.lunc_recipient)?,
+            minter_addr: api.addr_humanize(&self.minter_addr)?,
+            lunc_mint_ratio: self.lunc_mint_ratio,
+            ustc_mint_ratio: self.ustc_mint_ratio,
+            max_mint_ratio: self.max_mint_ratio,
+            max_mint_amount: self.max_mint_amount,
+            mint_price_band: self.mint_price_band.clone(),
+            max_supply: self.max_supply,
+            total_minted: self.total_minted,
+            allow_partial: self.allow_partial,
+            interval_seconds: self.interval_seconds,
+            reserve_balance: self.reserve_balance,
+            max_owed_months: self.max_owed_months,
+            treasury_addr: api.addr_humanize(&self.treasury_addr)?,
+            burn_threshold: self.burn_threshold,
+            high_burn_divisor: self.high_burn_divisor,
+            low_burn_divisor: self.low_burn_divisor,
+            burn_source: self.burn_source.clone(),
+            paused: self.paused,
+            pair_vest: self.pair_vest.to_normal(api)?,
+            nft_vest: self.nft_vest.to_normal(api)?,
+            marketing_vest: self.marketing_vest.to_normal(api)?,
+            game_vest: self.game_vest.to_normal(api)?,
+            team_vest: self.team_vest.to_normal(api)?,
+        })
+    }
+
+    pub fn vest(&self, bucket: Bucket) -> &VestInfoRaw {
+        match bucket {
+            Bucket::Pair => &self.pair_vest,
+            Bucket::Nft => &self.nft_vest,
+            Bucket::Marketing => &self.marketing_vest,
+            Bucket::Game => &self.game_vest,
+            Bucket::Team => &self.team_vest,
+        }
+    }
+
+    pub fn vest_mut(&mut self, bucket: Bucket) -> &mut VestInfoRaw {
+        match bucket {
+            Bucket::Pair => &mut self.pair_vest,
+            Bucket::Nft => &mut self.nft_vest,
+            Bucket::Marketing => &mut self.marketing_vest,
+            Bucket::Game => &mut self.game_vest,
+            Bucket::Team => &mut self.team_vest,
+        }
+    }
+}
@@ -48,6 +48,10 @@ pub enum QueryMsg {
     NativeTokenDecimals {
         denom: String,
     },
+    /// Pair whose liquidity token is `lp_token`
+    PairByLp {
+        lp_token: String,
+    },
 }
 
 // We define a custom struct for each query response
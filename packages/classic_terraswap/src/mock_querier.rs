@@ -1,8 +1,9 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
-    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+    from_binary, from_slice, to_binary, BankQuery, Coin, ContractResult, Decimal, OwnedDeps,
+    Querier, QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::panic;
@@ -10,9 +11,12 @@ use std::panic;
 use crate::asset::{AssetInfo, PairInfo};
 use crate::factory::{NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg};
 use crate::pair::QueryMsg as PairQueryMsg;
-use crate::pair::{ReverseSimulationResponse, SimulationResponse};
+use crate::pair::{PoolResponse, ReverseSimulationResponse, SimulationResponse};
 use classic_bindings::{SwapResponse, TaxCapResponse, TaxRateResponse, TerraQuery};
-use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use cw20::{
+    AllowanceResponse, BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, Expiration,
+    MinterResponse, TokenInfoResponse,
+};
 
 use std::iter::FromIterator;
 
@@ -37,18 +41,36 @@ pub struct WasmMockQuerier {
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
     terraswap_factory_querier: TerraswapFactoryQuerier,
+    // pair contract -> pool reserves
+    pools: HashMap<String, PoolResponse>,
+    // pair contract -> pair info, unknown pairs return a default uluna pair
+    pair_infos: HashMap<String, PairInfo>,
+    // native denoms the bank knows, the balance of any other denom fails,
+    // none configured means every denom is known
+    known_denoms: Option<Vec<String>>,
+    // queries received so far, to assert how many a handler makes
+    query_count: Cell<u64>,
 }
 
+// (owner, spender, allowance) granted on a token
+type AllowanceEntry<'a> = (&'a String, &'a String, &'a Uint128);
+
 #[derive(Clone, Default)]
 pub struct TokenQuerier {
     // this lets us iterate over all pairs that match the first string
     balances: HashMap<String, HashMap<String, Uint128>>,
+    // token contract -> (owner, spender) -> allowance
+    allowances: HashMap<String, HashMap<(String, String), Uint128>>,
+    // token contract -> minter
+    minters: HashMap<String, String>,
 }
 
 impl TokenQuerier {
     pub fn new(balances: &[(&String, &[(&String, &Uint128)])]) -> Self {
         TokenQuerier {
             balances: balances_to_map(balances),
+            allowances: HashMap::new(),
+            minters: HashMap::new(),
         }
     }
 }
@@ -130,6 +152,7 @@ pub(crate) fn native_token_decimals_to_map(
 
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        self.query_count.set(self.query_count.get() + 1);
         // MockQuerier doesn't support Custom, so we ignore it completely here
         let request: QueryRequest<TerraQuery> = match from_slice(bin_request) {
             Ok(v) => v,
@@ -192,6 +215,18 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                Ok(FactoryQueryMsg::PairByLp { lp_token }) => match self
+                    .terraswap_factory_querier
+                    .pairs
+                    .values()
+                    .find(|pair_info| pair_info.liquidity_token == lp_token)
+                {
+                    Some(v) => SystemResult::Ok(ContractResult::Ok(to_binary(v).unwrap())),
+                    None => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "No pair info exists".to_string(),
+                        request: msg.as_slice().into(),
+                    }),
+                },
                 Ok(FactoryQueryMsg::NativeTokenDecimals { denom }) => {
                     match self
                         .terraswap_factory_querier
@@ -211,8 +246,11 @@ impl WasmMockQuerier {
                     }
                 }
                 _ => match from_binary(msg) {
-                    Ok(PairQueryMsg::Pair {}) => {
-                        SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
+                    Ok(PairQueryMsg::Pair {}) => match self.pair_infos.get(contract_addr) {
+                        Some(pair_info) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(pair_info)))
+                        }
+                        None => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
                             asset_infos: [
                                 AssetInfo::NativeToken {
                                     denom: "uluna".to_string(),
@@ -224,8 +262,18 @@ impl WasmMockQuerier {
                             asset_decimals: [6u8, 6u8],
                             contract_addr: "pair0000".to_string(),
                             liquidity_token: "liquidity0000".to_string(),
-                        })))
-                    }
+                        }))),
+                    },
+                    Ok(PairQueryMsg::Pool {}) => match self.pools.get(contract_addr) {
+                        Some(pool) => SystemResult::Ok(ContractResult::from(to_binary(pool))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: format!(
+                                "No pool info exists for the contract {}",
+                                contract_addr
+                            ),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
                     Ok(PairQueryMsg::Simulation { offer_asset }) => {
                         SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
                             return_amount: offer_asset.amount,
@@ -303,11 +351,50 @@ impl WasmMockQuerier {
                                 to_binary(&Cw20BalanceResponse { balance }).unwrap(),
                             ))
                         }
+                        Cw20QueryMsg::Allowance { owner, spender } => {
+                            let allowance = self
+                                .token_querier
+                                .allowances
+                                .get(contract_addr)
+                                .and_then(|allowances| allowances.get(&(owner, spender)))
+                                .copied()
+                                .unwrap_or_default();
+
+                            SystemResult::Ok(ContractResult::Ok(
+                                to_binary(&AllowanceResponse {
+                                    allowance,
+                                    expires: Expiration::Never {},
+                                })
+                                .unwrap(),
+                            ))
+                        }
+                        Cw20QueryMsg::Minter {} => {
+                            match self.token_querier.minters.get(contract_addr) {
+                                Some(minter) => SystemResult::Ok(ContractResult::Ok(
+                                    to_binary(&Some(MinterResponse {
+                                        minter: minter.to_string(),
+                                        cap: None,
+                                    }))
+                                    .unwrap(),
+                                )),
+                                None => SystemResult::Err(SystemError::NoSuchContract {
+                                    addr: contract_addr.to_string(),
+                                }),
+                            }
+                        }
 
                         _ => panic!("DO NOT ENTER HERE"),
                     },
                 },
             },
+            QueryRequest::Bank(BankQuery::Balance { denom, .. })
+                if self
+                    .known_denoms
+                    .as_ref()
+                    .is_some_and(|known_denoms| !known_denoms.contains(denom)) =>
+            {
+                SystemResult::Ok(ContractResult::Err(format!("denom {} not found", denom)))
+            }
             _ => self.base.handle_query(request),
         }
     }
@@ -320,12 +407,43 @@ impl WasmMockQuerier {
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
             terraswap_factory_querier: TerraswapFactoryQuerier::default(),
+            pools: HashMap::new(),
+            pair_infos: HashMap::new(),
+            known_denoms: None,
+            query_count: Cell::new(0),
         }
     }
 
+    pub fn query_count(&self) -> u64 {
+        self.query_count.get()
+    }
+
     // configure the mint whitelist mock querier
     pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
-        self.token_querier = TokenQuerier::new(balances);
+        self.token_querier.balances = balances_to_map(balances);
+    }
+
+    // configure the allowances granted by (owner, spender) for each token
+    pub fn with_token_allowances(&mut self, allowances: &[(&String, &[AllowanceEntry])]) {
+        for (contract_addr, allowances) in allowances.iter() {
+            let contract_allowances = self
+                .token_querier
+                .allowances
+                .entry(contract_addr.to_string())
+                .or_default();
+            for (owner, spender, allowance) in allowances.iter() {
+                contract_allowances.insert((owner.to_string(), spender.to_string()), **allowance);
+            }
+        }
+    }
+
+    // configure the minter of each token
+    pub fn with_token_minters(&mut self, minters: &[(&String, &String)]) {
+        for (contract_addr, minter) in minters.iter() {
+            self.token_querier
+                .minters
+                .insert(contract_addr.to_string(), minter.to_string());
+        }
     }
 
     // configure the token owner mock querier
@@ -342,6 +460,26 @@ impl WasmMockQuerier {
         self.terraswap_factory_querier = TerraswapFactoryQuerier::new(pairs, native_token_decimals);
     }
 
+    // configure the reserves returned by the pair pool query
+    pub fn with_pools(&mut self, pools: &[(&String, &PoolResponse)]) {
+        for (pair_addr, pool) in pools.iter() {
+            self.pools.insert(pair_addr.to_string(), (*pool).clone());
+        }
+    }
+
+    // configure the info returned by the pair query of each pair contract
+    pub fn with_pair_infos(&mut self, pair_infos: &[(&String, &PairInfo)]) {
+        for (pair_addr, pair_info) in pair_infos.iter() {
+            self.pair_infos
+                .insert(pair_addr.to_string(), (*pair_info).clone());
+        }
+    }
+
+    // configure the native denoms known by the bank
+    pub fn with_known_denoms(&mut self, denoms: &[&str]) {
+        self.known_denoms = Some(denoms.iter().map(|denom| denom.to_string()).collect());
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.clone());
@@ -437,16 +575,65 @@ mod mock_exception {
     }
 
     #[test]
-    #[should_panic]
-    fn none_tokens_minter_will_panic() {
+    fn none_tokens_allowance_is_zero() {
+        let deps = mock_dependencies(&[]);
+
+        let msg = to_binary(&Cw20QueryMsg::Allowance {
+            owner: "owner0000".to_string(),
+            spender: "spender0000".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            deps.querier
+                .handle_query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: "token0000".to_string(),
+                    msg
+                })),
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&AllowanceResponse {
+                    allowance: Uint128::zero(),
+                    expires: Expiration::Never {},
+                })
+                .unwrap()
+            ))
+        )
+    }
+
+    #[test]
+    fn none_pair_pool_will_err() {
+        let deps = mock_dependencies(&[]);
+
+        let msg = to_binary(&PairQueryMsg::Pool {}).unwrap();
+
+        assert_eq!(
+            deps.querier
+                .handle_query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: "pair0000".to_string(),
+                    msg: msg.clone()
+                })),
+            SystemResult::Err(SystemError::InvalidRequest {
+                error: "No pool info exists for the contract pair0000".to_string(),
+                request: msg
+            })
+        )
+    }
+
+    #[test]
+    fn none_tokens_minter_will_err() {
         let deps = mock_dependencies(&[]);
 
         let msg = to_binary(&Cw20QueryMsg::Minter {}).unwrap();
 
-        deps.querier
-            .handle_query(&QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: "token0000".to_string(),
-                msg,
-            }));
+        assert_eq!(
+            deps.querier
+                .handle_query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: "token0000".to_string(),
+                    msg
+                })),
+            SystemResult::Err(SystemError::NoSuchContract {
+                addr: "token0000".to_string(),
+            })
+        )
     }
 }
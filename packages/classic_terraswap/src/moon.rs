@@ -1,14 +1,57 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use crate::asset::{Asset, AssetInfo, VestInfo};
 
-use cosmwasm_std::{Decimal, Uint128};
+#[cfg(feature = "debug")]
+use cosmwasm_std::Binary;
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
     pub clsm_addr: String,
+    /// First address allowed to trigger the scheduled operations, more can
+    /// be added through AddAuthorized
+    pub timer_trigger: String,
+    /// Recipient of the LUNC sent out by the contract
+    pub lunc_recipient: String,
+    /// CLSM minter which executes the dynamic mints
+    pub minter_addr: String,
+    /// Share of the pool value of the burned LUNC minted as CLSM
+    pub lunc_mint_ratio: Decimal,
+    /// Share of the pool value of the burned USTC minted as CLSM
+    pub ustc_mint_ratio: Decimal,
+    /// Highest mint ratio which can be set, defaults to 10
+    pub max_mint_ratio: Option<Decimal>,
+    /// Most CLSM a single dynamic mint may create
+    pub max_mint_amount: Uint128,
+    /// Pool prices the dynamic mints accept, any price when it is not set
+    pub mint_price_band: Option<PriceBand>,
+    /// CLSM supply the dynamic mints can never exceed
+    pub max_supply: Uint128,
+    /// Emit the available balance when it is below the monthly amount,
+    /// defaults to false
+    pub allow_partial: Option<bool>,
+    /// Minimum time between two emissions of a bucket, defaults to 30 days.
+    /// Each month of the vesting schedules lasts one interval
+    pub interval_seconds: Option<u64>,
+    /// CLSM the emissions always leave in the contract, defaults to zero
+    pub reserve_balance: Option<Uint128>,
+    /// Most months an underfunded bucket can carry forward, defaults to 6.
+    /// The months due past the cap are not owed
+    pub max_owed_months: Option<Uint128>,
+    /// Recipient of the CLSM left over once vesting is completed
+    pub treasury_addr: String,
+    /// Circulating supply above which the high burn divisor is used
+    pub burn_threshold: Uint128,
+    /// Fraction of the circulating supply burned above the threshold
+    pub high_burn_divisor: Uint128,
+    /// Fraction of the circulating supply burned below the threshold
+    pub low_burn_divisor: Uint128,
+    /// Holder of the CLSM taken by the automatic burn, defaults to the contract itself
+    pub burn_source: Option<BurnSource>,
     pub pair_vest: VestInfo,
     pub nft_vest: VestInfo,
     pub marketing_vest: VestInfo,
@@ -19,15 +62,185 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MoonExecuteMsg {
-    VestingMint {},
+    /// Run the emission of every bucket, only the pair or an authorized caller
+    /// can execute it. `to` sends this month's emissions to another address
+    /// than the configured recipients, only an authorized caller can set it
+    VestingMint {
+        to: Option<String>,
+    },
+    /// Run the emission of every bucket which is due and funded
+    EmitAll {
+        to: Option<String>,
+    },
+    /// Run the emission of a single bucket, only an authorized caller can execute it
+    Emit {
+        bucket: Bucket,
+        to: Option<String>,
+    },
     DynamicMintFromLunc {
         amount: Uint128,
-        price: Decimal
     },
     DynamicMintFromUstc {
         amount: Uint128,
-        price: Decimal
     },
+    /// Mint CLSM to the contract and provide it to the pair along with the
+    /// other pool asset held by the contract, only an authorized caller or
+    /// the minter can execute it
+    MintAndProvide {
+        clsm_amount: Uint128,
+        pair_asset_amount: Uint128,
+    },
+    SendLunc {
+        amount: Uint128,
+    },
+    AutomaticBurn {},
+    /// Send the CLSM no longer needed for vesting to the treasury
+    SweepCompletedVesting {
+        bucket: Bucket,
+    },
+    /// Update the dynamic mint ratios, only an authorized caller can execute it
+    UpdateMintRatios {
+        lunc_mint_ratio: Decimal,
+        ustc_mint_ratio: Decimal,
+    },
+    /// Update the pool prices the dynamic mints accept, only an authorized
+    /// caller can execute it. No band accepts any price
+    UpdateMintPriceBand {
+        band: Option<PriceBand>,
+    },
+    /// Update the automatic burn parameters, only an authorized caller can execute it
+    UpdateBurnParams {
+        burn_threshold: Uint128,
+        high_burn_divisor: Uint128,
+        low_burn_divisor: Uint128,
+    },
+    /// Stop or resume the emissions, mints and burns, only an authorized caller can
+    /// execute it
+    SetPaused {
+        paused: bool,
+    },
+    /// Send up to `amount` CLSM out of the contract, only an authorized
+    /// caller can execute it and only while the contract is paused
+    EmergencyWithdraw {
+        amount: Uint128,
+        to: String,
+    },
+    /// Propose an address to replace the sender in the authorized callers,
+    /// only an authorized caller can execute it. The sender stays authorized
+    /// until the proposal is accepted
+    UpdateTimerTrigger {
+        new_trigger: String,
+    },
+    /// Take over the authorization of the caller who proposed the sender,
+    /// only a proposed address can execute it
+    AcceptTimerTrigger {},
+    /// Allow one more address to trigger the scheduled operations, only an
+    /// authorized caller can execute it
+    AddAuthorized {
+        address: String,
+    },
+    /// Revoke an authorized caller, the last one can't be removed
+    RemoveAuthorized {
+        address: String,
+    },
+    /// Point the dynamic mint at a new CLSM minter, only an authorized caller
+    /// can execute it. VerifyMinter checks it against the token afterwards
+    UpdateMinterAddr {
+        new_minter: String,
+    },
+    /// Change the recipient of a vesting bucket, keeping its schedule
+    UpdateVestRecipient {
+        bucket: Bucket,
+        new_address: String,
+    },
+    /// Move the bucket to its next month without emitting, only an
+    /// authorized caller can execute it
+    SkipMonth {
+        bucket: Bucket,
+    },
+    /// Reserve part of the contract balance for the bucket, only an
+    /// authorized caller can execute it
+    RegisterFunding {
+        bucket: Bucket,
+        amount: Uint128,
+    },
+    /// Change the holder of the CLSM taken by the automatic burn, only an
+    /// authorized caller can execute it
+    UpdateBurnSource {
+        burn_source: BurnSource,
+    },
+    /// Check that the CLSM token is minted by the configured minter, which
+    /// the mint messages are sent to
+    VerifyMinter {},
+    /// Send the CLSM which is not reserved for the vesting to several
+    /// recipients, only an authorized caller can execute it
+    BatchTransfer {
+        transfers: Vec<(String, Uint128)>,
+    },
+    Receive(Cw20ReceiveMsg),
+}
+
+/// Holder of the CLSM taken by the automatic burn. The pair and the custom
+/// Range of the pool price of the burned denom, in CLSM per unit, within
+/// which a dynamic mint runs. A price moved out of it within the same
+/// transaction fails the mint
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceBand {
+    pub min: Decimal,
+    pub max: Decimal,
+}
+
+/// holder must allow the contract to burn from their balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnSource {
+    ContractSelf,
+    Pair,
+    Custom(Addr),
+}
+
+impl fmt::Display for BurnSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BurnSource::ContractSelf => write!(f, "contract_self"),
+            BurnSource::Pair => write!(f, "pair"),
+            BurnSource::Custom(addr) => write!(f, "{}", addr),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Bucket {
+    Pair,
+    Nft,
+    Marketing,
+    Game,
+    Team,
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bucket::Pair => write!(f, "pair"),
+            Bucket::Nft => write!(f, "nft"),
+            Bucket::Marketing => write!(f, "marketing"),
+            Bucket::Game => write!(f, "game"),
+            Bucket::Team => write!(f, "team"),
+        }
+    }
+}
+
+impl Bucket {
+    pub fn all() -> [Bucket; 5] {
+        [
+            Bucket::Pair,
+            Bucket::Nft,
+            Bucket::Marketing,
+            Bucket::Game,
+            Bucket::Team,
+        ]
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -36,6 +249,182 @@ pub enum Cw20HookMsg {
     SendToken {
         amount: Uint128
     },
+    /// Burn the CLSM sent, counted in the burn stats
+    Burn {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Configuration with the addresses humanized
+    Config {},
+    VestingStatus {},
+    /// Whether the bucket can emit now, without executing the emission
+    CanEmit { bucket: Bucket },
+    /// Time left before the emission interval of the bucket has passed
+    NextEmission { bucket: Bucket },
+    /// CanEmit of every bucket, in the order EmitAll emits them
+    AllEmissionStatus {},
+    /// CLSM supply figures driving the automatic burn
+    TokenStats {},
+    /// CLSM price in the other asset of the funded pair
+    ClsmPrice {},
+    BurnSource {},
+    /// Remaining emissions of the bucket, one entry per month
+    EmissionSchedule {
+        bucket: Bucket,
+        max_entries: u32,
+    },
+    /// CLSM burned by the holders through the contract
+    BurnStats {},
+    /// All the CLSM burned through the contract, split between the
+    /// automatic and the holder burns
+    TotalBurned {},
+    /// Past emissions of the bucket, keyed by the month they completed
+    EmissionHistory {
+        bucket: Bucket,
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    },
+    /// Stored addresses in both their canonical and humanized forms, only
+    /// built with the debug feature
+    #[cfg(feature = "debug")]
+    RawConfig {},
+}
+
+/// Data set on the automatic burn response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AutomaticBurnResponse {
+    pub burn_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TokenStatsResponse {
+    /// Circulating supply, excluding the contract and burn address balances
+    pub total_supply: Uint128,
+    pub contract_balance: Uint128,
+    pub next_burn_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CanEmitResponse {
+    pub ok: bool,
+    /// Why the emission would fail
+    pub reason: Option<String>,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EmissionStatus {
+    pub bucket: Bucket,
+    pub ready: bool,
+    pub amount: Uint128,
+    /// Why the emission would fail
+    pub reason: Option<String>,
+}
+
+/// The buckets ready earlier spend the balance, as they would in EmitAll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AllEmissionStatusResponse {
+    pub statuses: Vec<EmissionStatus>,
+}
+
+/// Only the emission interval is considered, CanEmit covers the funding and
+/// the completed schedules
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NextEmissionResponse {
+    pub ready: bool,
+    pub seconds_remaining: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnStatsResponse {
+    pub total_burned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TotalBurnedResponse {
+    pub total_burned: Uint128,
+    pub automatic_burned: Uint128,
+    /// Burned by the holders through the receive hook
+    pub voluntary_burned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnSourceResponse {
+    pub burn_source: BurnSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ClsmPriceResponse {
+    pub price: Decimal,
+    /// Asset the price is denominated in
+    pub quote_asset_info: AssetInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EmissionScheduleEntry {
+    pub month_index: Uint128,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EmissionScheduleResponse {
+    pub entries: Vec<EmissionScheduleEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EmissionRecord {
+    /// Month index of the bucket once the emission was recorded
+    pub month_index: Uint128,
+    pub timestamp: u64,
+    pub amount: Uint128,
+    pub recipient: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct EmissionHistoryResponse {
+    pub records: Vec<EmissionRecord>,
+}
+
+#[cfg(feature = "debug")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RawAddress {
+    pub field: String,
+    /// Stored bytes, base64 encoded
+    pub canonical: Binary,
+    /// None when the stored bytes don't humanize
+    pub humanized: Option<Addr>,
+}
+
+#[cfg(feature = "debug")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RawConfigResponse {
+    pub addresses: Vec<RawAddress>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingStatus {
+    pub month_index: Uint128,
+    pub month_count: Uint128,
+    pub monthly_amount: Uint128,
+    /// Amount still to be emitted
+    pub remaining: Uint128,
+    /// Amount the partial emissions fell short of the monthly amount
+    pub shortfall: Uint128,
+    /// Months missed for lack of funds, released by a later emission
+    pub months_owed: Uint128,
+    /// Balance registered for the bucket alone
+    pub funding: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingStatusResponse {
+    pub pair: VestingStatus,
+    pub nft: VestingStatus,
+    pub marketing: VestingStatus,
+    pub game: VestingStatus,
+    pub team: VestingStatus,
 }
 
 // We define a custom struct for each query response
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::asset::{Asset, AssetInfo};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Decimal, Decimal256, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -15,6 +15,15 @@ pub struct InstantiateMsg {
     pub asset_decimals: [u8; 2],
     pub clsm_addr: String,
     pub team_addr: String,
+    /// Commission rate charged on swaps, defaults to 0.2%
+    pub commission_rate: Option<Decimal>,
+    /// Share of the commission sent to the team, defaults to 50%
+    pub protocol_fee_fraction: Option<Decimal>,
+    /// LP tokens locked at the first provision, defaults to 1000
+    pub minimum_liquidity: Option<Uint128>,
+    /// Count the token amounts the pair actually receives rather than the
+    /// stated ones, for tokens taking a fee on transfer. Defaults to false
+    pub measure_received: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -26,7 +35,12 @@ pub enum ExecuteMsg {
         assets: [Asset; 2],
         receiver: Option<String>,
         deadline: Option<u64>,
+        /// Largest share of either deposit left unused because the deposits
+        /// deviate from the pool ratio. The unused native amount is refunded
+        /// and the unused token amount is never transferred
         slippage_tolerance: Option<Decimal>,
+        /// Minimum amount of LP tokens to receive
+        min_lp_tokens: Option<Uint128>,
     },
     /// Swap an offer asset to the other
     Swap {
@@ -39,6 +53,30 @@ pub enum ExecuteMsg {
     SetMoonAddress {
         moon_addr: String
     },
+    /// Update the swap commission rate, only the team can execute it
+    UpdateCommission {
+        commission_rate: Decimal,
+    },
+    /// Propose a new team address, only the current team can execute it.
+    /// The current team keeps control until the proposal is accepted
+    ProposeAdmin {
+        new: String,
+    },
+    /// Take over the team address, only the proposed address can execute it
+    AcceptAdmin {},
+    /// Send the whole balance of stray assets to the team, only the team can
+    /// execute it. The pool assets, the LP token and CLSM can't be collected
+    CollectDust {
+        assets: Vec<AssetInfo>,
+    },
+    /// Send the accrued team share of the commissions to the team, only the
+    /// team can execute it
+    ClaimFees {},
+    /// Fold the balances above the stored reserves into the reserve ledger,
+    /// only the team can execute it. The ledger is never lowered. Swaps and
+    /// withdrawals price from the balances, which already hold any donation,
+    /// so only the audit and the measured swaps see the change
+    SyncReserves {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -50,6 +88,8 @@ pub enum Cw20HookMsg {
         max_spread: Option<Decimal>,
         to: Option<String>,
         deadline: Option<u64>,
+        /// Minimum amount of the ask asset to receive
+        minimum_receive: Option<Uint128>,
     },
     WithdrawLiquidity {
         min_assets: Option<[Asset; 2]>,
@@ -63,10 +103,23 @@ pub enum QueryMsg {
     Pair {},
     Pool {},
     Simulation { offer_asset: Asset },
+    /// Simulation along with the decimals of both assets
+    SimulationV2 { offer_asset: Asset },
     ReverseSimulation { ask_asset: Asset },
     TotalSupply {},
     LuncDynamicMinting {},
     UstcDynamicMinting {},
+    SpotPrice {},
+    CumulativePrices {},
+    Config {},
+    /// Assets returned by withdrawing the given amount of LP tokens
+    SimulateWithdraw {
+        lp_amount: Uint128,
+    },
+    /// Team share of the commissions not claimed yet
+    AccruedFees {},
+    /// Stored reserves checked against the balances of the pair
+    ReserveAudit {},
 }
 
 // We define a custom struct for each query response
@@ -76,6 +129,21 @@ pub struct PoolResponse {
     pub total_share: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigResponse {
+    pub team_addr: String,
+    pub mint_count: u64,
+    pub burn_count: u64,
+    pub lunc_dynamic_mint: bool,
+    pub ustc_dynamic_mint: bool,
+    pub clsm_addr: String,
+    pub moon_addr: Option<String>,
+    pub commission_rate: Decimal,
+    pub protocol_fee_fraction: Decimal,
+    pub minimum_liquidity: Uint128,
+    pub measure_received: bool,
+}
+
 /// SimulationResponse returns swap simulation response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct SimulationResponse {
@@ -84,6 +152,57 @@ pub struct SimulationResponse {
     pub commission_amount: Uint128,
 }
 
+/// SimulationResponseV2 returns swap simulation response with the
+/// decimals needed to normalize the amounts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulationResponseV2 {
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+    pub offer_decimals: u8,
+    pub ask_decimals: u8,
+}
+
+/// SpotPriceResponse returns the price of each asset computed from the pool reserves
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SpotPriceResponse {
+    pub asset_0_per_1: Decimal,
+    pub asset_1_per_0: Decimal,
+}
+
+/// CumulativePricesResponse returns the price accumulators of the pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CumulativePricesResponse {
+    pub price_0_cumulative_last: Decimal256,
+    pub price_1_cumulative_last: Decimal256,
+    pub block_time_last: u64,
+}
+
+/// SimulateWithdrawResponse returns the assets refunded for the LP amount
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateWithdrawResponse {
+    pub asset_0: Asset,
+    pub asset_1: Asset,
+}
+
+/// AccruedFeesResponse returns the team share of the commissions not claimed
+/// yet, in the order of the pair assets
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AccruedFeesResponse {
+    pub accrued_fees: [Asset; 2],
+}
+
+/// ReserveAuditResponse compares the stored reserves with the balances of
+/// the pair. The balances are expected to hold the reserves and the accrued
+/// fees, a surplus is a donation and a shortfall a bookkeeping error
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ReserveAuditResponse {
+    pub reserves: [Asset; 2],
+    pub balances: [Asset; 2],
+    pub surplus: [Uint128; 2],
+    pub shortfall: [Uint128; 2],
+}
+
 /// ReverseSimulationResponse returns reverse swap simulation response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ReverseSimulationResponse {
@@ -1,6 +1,8 @@
 use crate::asset::{Asset, AssetInfo, PairInfo};
 use crate::factory::{NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg};
-use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
+use crate::pair::{
+    PoolResponse, QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse,
+};
 
 use classic_bindings::TerraQuery;
 use cosmwasm_std::{
@@ -8,7 +10,9 @@ use cosmwasm_std::{
     QueryRequest, StdResult, Uint128, WasmQuery,
 };
 
-use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use cw20::{
+    BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, MinterResponse, TokenInfoResponse,
+};
 
 pub fn query_balance(
     querier: &QuerierWrapper<TerraQuery>,
@@ -23,6 +27,20 @@ pub fn query_balance(
     Ok(balance.amount.amount)
 }
 
+/// Balance of the denom, zero when the bank has never seen the denom. It is
+/// picked out of all the balances, which are answered for any account
+pub fn query_balance_or_zero(
+    querier: &QuerierWrapper<TerraQuery>,
+    account_addr: Addr,
+    denom: String,
+) -> StdResult<Uint128> {
+    Ok(query_all_balances(querier, account_addr)?
+        .into_iter()
+        .find(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .unwrap_or_default())
+}
+
 pub fn query_all_balances(
     querier: &QuerierWrapper<TerraQuery>,
     account_addr: Addr,
@@ -63,6 +81,17 @@ pub fn query_token_info(
     Ok(token_info)
 }
 
+/// Minter of the token, none when the supply is fixed
+pub fn query_token_minter(
+    querier: &QuerierWrapper<TerraQuery>,
+    contract_addr: Addr,
+) -> StdResult<Option<MinterResponse>> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::Minter {})?,
+    }))
+}
+
 pub fn query_native_decimals(
     querier: &QuerierWrapper<TerraQuery>,
     factory_contract: Addr,
@@ -76,6 +105,62 @@ pub fn query_native_decimals(
     Ok(res.decimals)
 }
 
+/// Decimals assumed for a native denom, the bank keeps no metadata
+pub const DEFAULT_NATIVE_DECIMALS: u8 = 6;
+
+/// Decimals of the asset without going through the factory, the default
+/// for a native denom and the token info for a token
+pub fn query_decimals(querier: &QuerierWrapper<TerraQuery>, asset: &AssetInfo) -> StdResult<u8> {
+    DecimalsCache::default().query_decimals(querier, asset)
+}
+
+/// Decimals of the assets already queried, so normalizing several amounts
+/// of the same token queries its info once
+#[derive(Clone, Debug, Default)]
+pub struct DecimalsCache {
+    native_decimals: Vec<(String, u8)>,
+    cached: Vec<(AssetInfo, u8)>,
+}
+
+impl DecimalsCache {
+    /// Cache using the given decimals for these native denoms instead of
+    /// the default
+    pub fn new(native_decimals: &[(&str, u8)]) -> Self {
+        DecimalsCache {
+            native_decimals: native_decimals
+                .iter()
+                .map(|(denom, decimals)| (denom.to_string(), *decimals))
+                .collect(),
+            cached: vec![],
+        }
+    }
+
+    pub fn query_decimals(
+        &mut self,
+        querier: &QuerierWrapper<TerraQuery>,
+        asset: &AssetInfo,
+    ) -> StdResult<u8> {
+        if let Some((_, decimals)) = self.cached.iter().find(|(cached, _)| cached == asset) {
+            return Ok(*decimals);
+        }
+
+        let decimals = match asset {
+            AssetInfo::NativeToken { denom } => self
+                .native_decimals
+                .iter()
+                .find(|(native, _)| native == denom)
+                .map(|(_, decimals)| *decimals)
+                .unwrap_or(DEFAULT_NATIVE_DECIMALS),
+            AssetInfo::Token { contract_addr } => {
+                query_token_info(querier, Addr::unchecked(contract_addr))?.decimals
+            }
+        };
+        self.cached.push((asset.clone(), decimals));
+
+        Ok(decimals)
+    }
+}
+
 pub fn query_pair_info(
     querier: &QuerierWrapper<TerraQuery>,
     factory_contract: Addr,
@@ -89,6 +174,31 @@ pub fn query_pair_info(
     }))
 }
 
+/// Pair the factory created for the two assets, for callers that only know
+/// the assets and not the pair address. Same lookup as `query_pair_info`,
+/// taking the assets by value
+pub fn query_pair_by_assets(
+    querier: &QuerierWrapper<TerraQuery>,
+    factory_contract: Addr,
+    asset_infos: [AssetInfo; 2],
+) -> StdResult<PairInfo> {
+    query_pair_info(querier, factory_contract, &asset_infos)
+}
+
+/// Pair the factory created for the given liquidity token
+pub fn query_pair_by_lp(
+    querier: &QuerierWrapper<TerraQuery>,
+    factory_contract: Addr,
+    lp_token: Addr,
+) -> StdResult<PairInfo> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::PairByLp {
+            lp_token: lp_token.to_string(),
+        })?,
+    }))
+}
+
 pub fn simulate(
     querier: &QuerierWrapper<TerraQuery>,
     pair_contract: Addr,
@@ -126,3 +236,32 @@ pub fn query_pair_info_from_pair(
 
     Ok(pair_info)
 }
+
+/// Queries the info of each pair in order, a failing pair is skipped
+/// when `skip_failed` is set and returns the error otherwise
+pub fn query_pairs_info(
+    querier: &QuerierWrapper<TerraQuery>,
+    pair_contracts: Vec<Addr>,
+    skip_failed: bool,
+) -> StdResult<Vec<PairInfo>> {
+    let mut pairs_info: Vec<PairInfo> = vec![];
+    for pair_contract in pair_contracts {
+        match query_pair_info_from_pair(querier, pair_contract) {
+            Ok(pair_info) => pairs_info.push(pair_info),
+            Err(_) if skip_failed => continue,
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(pairs_info)
+}
+
+pub fn query_pool(
+    querier: &QuerierWrapper<TerraQuery>,
+    pair_contract: Addr,
+) -> StdResult<PoolResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_contract.to_string(),
+        msg: to_binary(&PairQueryMsg::Pool {})?,
+    }))
+}
@@ -1,7 +1,11 @@
-use crate::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw, PairInfo};
+use crate::asset::{
+    assert_sent_native_token_balances, Asset, AssetInfo, AssetInfoRaw, AssetRaw, PairInfo,
+};
 use crate::mock_querier::mock_dependencies;
 use crate::querier::{
-    query_all_balances, query_balance, query_pair_info, query_token_balance, query_token_info,
+    query_all_balances, query_balance, query_balance_or_zero, query_decimals, query_pair_by_assets,
+    query_pair_info, query_pairs_info, query_token_balance, query_token_info, DecimalsCache,
+    DEFAULT_NATIVE_DECIMALS,
 };
 
 use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
@@ -49,6 +53,46 @@ fn balance_querier() {
     );
 }
 
+#[test]
+fn balance_or_zero_querier() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::from(200u128),
+    }]);
+    deps.querier.with_known_denoms(&["uluna"]);
+
+    // the bank fails on a denom it has never seen
+    let err = query_balance(
+        &deps.as_ref().querier,
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        "uusd".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Querier contract error: denom uusd not found")
+    );
+
+    assert_eq!(
+        query_balance_or_zero(
+            &deps.as_ref().querier,
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            "uusd".to_string(),
+        )
+        .unwrap(),
+        Uint128::zero()
+    );
+    assert_eq!(
+        query_balance_or_zero(
+            &deps.as_ref().querier,
+            Addr::unchecked(MOCK_CONTRACT_ADDR),
+            "uluna".to_string(),
+        )
+        .unwrap(),
+        Uint128::from(200u128)
+    );
+}
+
 #[test]
 fn all_balances_querier() {
     let deps = mock_dependencies(&[
@@ -99,6 +143,43 @@ fn supply_querier() {
     )
 }
 
+#[test]
+fn decimals_querier() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_token_balances(&[(&"asset0000".to_string(), &[])]);
+    let querier = deps.as_ref().querier;
+
+    let native = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let token = AssetInfo::Token {
+        contract_addr: "asset0000".to_string(),
+    };
+
+    // a native denom never queries the chain
+    let queries = deps.querier.query_count();
+    assert_eq!(
+        query_decimals(&querier, &native).unwrap(),
+        DEFAULT_NATIVE_DECIMALS
+    );
+    assert_eq!(deps.querier.query_count(), queries);
+    assert_eq!(query_decimals(&querier, &token).unwrap(), 8);
+
+    // the cache uses the configured denoms and queries each token once
+    let mut cache = DecimalsCache::new(&[("uluna", 8)]);
+    let luna = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    assert_eq!(cache.query_decimals(&querier, &luna).unwrap(), 8);
+    assert_eq!(cache.query_decimals(&querier, &native).unwrap(), 6);
+
+    let queries = deps.querier.query_count();
+    assert_eq!(cache.query_decimals(&querier, &token).unwrap(), 8);
+    assert_eq!(cache.query_decimals(&querier, &token).unwrap(), 8);
+    assert_eq!(deps.querier.query_count() - queries, 1);
+}
+
 #[test]
 fn test_asset_info() {
     let token_info: AssetInfo = AssetInfo::Token {
@@ -298,6 +379,72 @@ fn test_assert_sent_native_token_balance() {
     )
 }
 
+#[test]
+fn test_assert_sent_native_token_balance_funds() {
+    let uluna_asset = Asset {
+        amount: Uint128::from(123u128),
+        info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    };
+    let token_asset = Asset {
+        amount: Uint128::from(123u128),
+        info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+    };
+    let message_info = |funds: Vec<Coin>| MessageInfo {
+        funds,
+        sender: Addr::unchecked("addr0000"),
+    };
+    let mismatch = Err(StdError::generic_err(
+        "Native token balance mismatch between the argument and the transferred",
+    ));
+
+    // correct funds
+    assert_eq!(
+        uluna_asset.assert_sent_native_token_balance(&message_info(vec![coin(123, "uluna")])),
+        Ok(())
+    );
+    assert_eq!(
+        token_asset.assert_sent_native_token_balance(&message_info(vec![])),
+        Ok(())
+    );
+
+    // missing funds
+    assert_eq!(
+        uluna_asset.assert_sent_native_token_balance(&message_info(vec![])),
+        mismatch
+    );
+
+    // extra denoms
+    assert_eq!(
+        uluna_asset.assert_sent_native_token_balance(&message_info(vec![
+            coin(123, "uluna"),
+            coin(1, "uusd")
+        ])),
+        Err(StdError::generic_err("Unexpected native token sent: uusd"))
+    );
+    assert_eq!(
+        token_asset.assert_sent_native_token_balance(&message_info(vec![coin(123, "uluna")])),
+        Err(StdError::generic_err("Unexpected native token sent: uluna"))
+    );
+
+    // a provide checks both assets against the same funds
+    let assets = [uluna_asset, token_asset];
+    assert_eq!(
+        assert_sent_native_token_balances(&assets, &message_info(vec![coin(123, "uluna")])),
+        Ok(())
+    );
+    assert_eq!(
+        assert_sent_native_token_balances(
+            &assets,
+            &message_info(vec![coin(123, "uluna"), coin(1, "uusd")])
+        ),
+        Err(StdError::generic_err("Unexpected native token sent: uusd"))
+    );
+}
+
 #[test]
 fn test_asset_to_raw() {
     let deps = mock_dependencies(&[]);
@@ -385,6 +532,32 @@ fn test_asset_info_raw_equal() {
     }));
 }
 
+#[test]
+fn test_asset_info_to_normalized() {
+    let deps = mock_dependencies(&[]);
+
+    // the same token with a differently cased address
+    let token_info = AssetInfo::Token {
+        contract_addr: "contract0000".to_string(),
+    };
+    let upper_token_info = AssetInfo::Token {
+        contract_addr: "CONTRACT0000".to_string(),
+    };
+    assert!(!token_info.equal(&upper_token_info));
+
+    let normalized = upper_token_info.to_normalized(&deps.api).unwrap();
+    assert_eq!(normalized, token_info);
+    assert!(token_info.equal(&normalized));
+
+    // a native token never matches a token
+    let native_info = AssetInfo::NativeToken {
+        denom: "contract0000".to_string(),
+    };
+    let normalized = native_info.to_normalized(&deps.api).unwrap();
+    assert_eq!(normalized, native_info);
+    assert!(!token_info.equal(&normalized));
+}
+
 #[test]
 fn query_terraswap_pair_contract() {
     let mut deps = mock_dependencies(&[]);
@@ -426,3 +599,95 @@ fn query_terraswap_pair_contract() {
     assert_eq!(pair_info.contract_addr, Addr::unchecked("pair0000"),);
     assert_eq!(pair_info.liquidity_token, Addr::unchecked("liquidity0000"),);
 }
+
+#[test]
+fn query_terraswap_pair_by_assets() {
+    let mut deps = mock_dependencies(&[]);
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    ];
+    deps.querier.with_terraswap_factory(
+        &[(
+            &"asset0000uusd".to_string(),
+            &PairInfo {
+                asset_infos: asset_infos.clone(),
+                contract_addr: "pair0000".to_string(),
+                liquidity_token: "liquidity0000".to_string(),
+                asset_decimals: [6u8, 6u8],
+            },
+        )],
+        &[("uusd".to_string(), 6u8)],
+    );
+
+    let pair_info = query_pair_by_assets(
+        &deps.as_ref().querier,
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        asset_infos.clone(),
+    )
+    .unwrap();
+    assert_eq!(pair_info.contract_addr, "pair0000".to_string());
+    assert_eq!(pair_info.asset_infos, asset_infos);
+
+    // the lookup does not depend on the asset order
+    let pair_info = query_pair_by_assets(
+        &deps.as_ref().querier,
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        [asset_infos[1].clone(), asset_infos[0].clone()],
+    )
+    .unwrap();
+    assert_eq!(pair_info.contract_addr, "pair0000".to_string());
+
+    // no pair was created for these assets
+    query_pair_by_assets(
+        &deps.as_ref().querier,
+        Addr::unchecked(MOCK_CONTRACT_ADDR),
+        [
+            AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn query_pairs_info_in_order() {
+    let mut deps = mock_dependencies(&[]);
+
+    let pair_info = |contract_addr: &str, token: &str| PairInfo {
+        asset_infos: [
+            AssetInfo::Token {
+                contract_addr: token.to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+        contract_addr: contract_addr.to_string(),
+        liquidity_token: format!("liquidity_{}", contract_addr),
+        asset_decimals: [6u8, 6u8],
+    };
+    let pair0 = pair_info("pair0000", "asset0000");
+    let pair1 = pair_info("pair0001", "asset0001");
+    deps.querier.with_pair_infos(&[
+        (&"pair0000".to_string(), &pair0),
+        (&"pair0001".to_string(), &pair1),
+    ]);
+
+    let pairs_info = query_pairs_info(
+        &deps.as_ref().querier,
+        vec![Addr::unchecked("pair0001"), Addr::unchecked("pair0000")],
+        false,
+    )
+    .unwrap();
+    assert_eq!(pairs_info, vec![pair1, pair0]);
+}
@@ -1,6 +1,16 @@
 use classic_bindings::TerraQuery;
-use cosmwasm_std::{DepsMut, StdError, StdResult};
+use cosmwasm_std::{attr, Attribute, Decimal256, DepsMut, StdError, StdResult, Uint128, Uint256};
 use cw2::{get_contract_version, set_contract_version};
+use std::cmp::Ordering;
+
+/// Version of the event attributes, to bump whenever their keys change
+pub const SCHEMA_VERSION: &str = "1";
+
+/// Attributes added to every emission, burn and swap response, so the
+/// indexers can tell the attribute schemas apart
+pub fn base_attrs() -> Vec<Attribute> {
+    vec![attr("schema_version", SCHEMA_VERSION)]
+}
 
 pub fn assert_deadline(blocktime: u64, deadline: Option<u64>) -> StdResult<()> {
     if let Some(deadline) = deadline {
@@ -12,6 +22,26 @@ pub fn assert_deadline(blocktime: u64, deadline: Option<u64>) -> StdResult<()> {
     Ok(())
 }
 
+/// Same as assert_deadline, but a block at most grace_seconds past the
+/// deadline is still accepted. Without grace it is as strict as assert_deadline
+pub fn assert_deadline_with_grace(
+    blocktime: u64,
+    deadline: Option<u64>,
+    grace_seconds: u64,
+) -> StdResult<()> {
+    if grace_seconds == 0 {
+        return assert_deadline(blocktime, deadline);
+    }
+
+    if let Some(deadline) = deadline {
+        if blocktime > deadline.saturating_add(grace_seconds) {
+            return Err(StdError::generic_err("Expired deadline"));
+        }
+    }
+
+    Ok(())
+}
+
 pub fn migrate_version(
     deps: DepsMut<TerraQuery>,
     target_contract_version: &str,
@@ -23,6 +53,11 @@ pub fn migrate_version(
         return Err(StdError::generic_err("invalid contract"));
     }
 
+    // a contract already at the version can be migrated again
+    if prev_version.version == version {
+        return Ok(());
+    }
+
     if prev_version.version != target_contract_version {
         return Err(StdError::generic_err(format!(
             "invalid contract version. target {}, but source is {}",
@@ -35,6 +70,52 @@ pub fn migrate_version(
     Ok(())
 }
 
+fn parse_version(version: &str) -> StdResult<Vec<u64>> {
+    version
+        .split('.')
+        .map(|v| {
+            v.parse::<u64>()
+                .map_err(|_| StdError::generic_err(format!("invalid version {}", version)))
+        })
+        .collect()
+}
+
+pub fn assert_no_downgrade(prev_version: &str, version: &str) -> StdResult<()> {
+    if parse_version(prev_version)?.cmp(&parse_version(version)?) == Ordering::Greater {
+        return Err(StdError::generic_err(format!(
+            "cannot downgrade from {} to {}",
+            prev_version, version
+        )));
+    }
+
+    Ok(())
+}
+
+/// Direction in which a decimal value is rounded back to an amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+}
+
+/// Amount of an asset with the given decimals in whole units
+pub fn to_decimal(amount: Uint128, decimals: u8) -> StdResult<Decimal256> {
+    let unit = Uint256::from(10u8).checked_pow(decimals.into())?;
+    Ok(Decimal256::from_ratio(amount, unit))
+}
+
+/// Amount of an asset with the given decimals for the value in whole units.
+/// Amounts owed to the pool round up, amounts paid to a user round down
+pub fn from_decimal(value: Decimal256, decimals: u8, rounding: Rounding) -> StdResult<Uint128> {
+    let unit = Uint256::from(10u8).checked_pow(decimals.into())?;
+    let mut amount: Uint256 = unit * value;
+    if rounding == Rounding::Ceil && Decimal256::from_ratio(amount, unit) != value {
+        amount += Uint256::one();
+    }
+
+    Ok(amount.try_into()?)
+}
+
 #[test]
 fn test_assert_deadline_with_normal() {
     assert_deadline(5u64, Some(10u64)).unwrap();
@@ -57,6 +138,76 @@ fn test_assert_deadline_with_none() {
     assert_deadline(5u64, None).unwrap();
 }
 
+#[test]
+fn test_assert_deadline_with_grace() {
+    assert_deadline_with_grace(13u64, Some(10u64), 3u64).unwrap();
+
+    let err = assert_deadline_with_grace(14u64, Some(10u64), 3u64).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Expired deadline"));
+
+    // no grace is as strict as assert_deadline
+    let err = assert_deadline_with_grace(10u64, Some(10u64), 0u64).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Expired deadline"));
+    assert_deadline_with_grace(9u64, Some(10u64), 0u64).unwrap();
+    assert_deadline_with_grace(u64::MAX, None, 3u64).unwrap();
+}
+
+#[test]
+fn test_assert_no_downgrade() {
+    assert_no_downgrade("0.1.1", "0.2.0").unwrap();
+    assert_no_downgrade("0.2.0", "0.2.0").unwrap();
+    assert_no_downgrade("0.9.0", "0.10.0").unwrap();
+
+    let err = assert_no_downgrade("0.2.0", "0.1.1").unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("cannot downgrade from 0.2.0 to 0.1.1")
+    );
+
+    let err = assert_no_downgrade("0.x", "0.1.1").unwrap_err();
+    assert_eq!(err, StdError::generic_err("invalid version 0.x"));
+}
+
+#[test]
+fn test_decimal_rounding() {
+    use std::str::FromStr;
+
+    let value = to_decimal(Uint128::from(1_500_000u128), 6).unwrap();
+    assert_eq!(value, Decimal256::from_str("1.5").unwrap());
+
+    // half a unit rounds down for the user and up for the pool
+    assert_eq!(
+        from_decimal(value, 0, Rounding::Floor).unwrap(),
+        Uint128::from(1u128)
+    );
+    assert_eq!(
+        from_decimal(value, 0, Rounding::Ceil).unwrap(),
+        Uint128::from(2u128)
+    );
+
+    // half of the smallest unit of a 6 decimals asset
+    let value = Decimal256::from_str("0.0000005").unwrap();
+    assert_eq!(
+        from_decimal(value, 6, Rounding::Floor).unwrap(),
+        Uint128::zero()
+    );
+    assert_eq!(
+        from_decimal(value, 6, Rounding::Ceil).unwrap(),
+        Uint128::from(1u128)
+    );
+
+    // exact values are not rounded
+    let value = Decimal256::from_str("1.5").unwrap();
+    assert_eq!(
+        from_decimal(value, 6, Rounding::Floor).unwrap(),
+        Uint128::from(1_500_000u128)
+    );
+    assert_eq!(
+        from_decimal(value, 6, Rounding::Ceil).unwrap(),
+        Uint128::from(1_500_000u128)
+    );
+}
+
 #[cfg(test)]
 mod test {
     use crate::mock_querier::mock_dependencies;
@@ -83,6 +234,21 @@ mod test {
         assert_eq!(version.version, CURRENT_VERSION);
     }
 
+    #[test]
+    pub fn repeated_migration() {
+        let mut deps = mock_dependencies(&[]);
+        set_contract_version(deps.as_mut().storage, NAME, TARGET_VERSION).unwrap();
+
+        migrate_version(deps.as_mut(), TARGET_VERSION, NAME, CURRENT_VERSION).unwrap();
+        let res = migrate_version(deps.as_mut(), TARGET_VERSION, NAME, CURRENT_VERSION);
+
+        assert_eq!(res, Ok(()));
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+
+        assert_eq!(version.version, CURRENT_VERSION);
+    }
+
     #[test]
     pub fn failed_migration_with_invalid_contract_name() {
         let mut deps = mock_dependencies(&[]);